@@ -0,0 +1,735 @@
+//! Async CoreApi
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{self, header::HeaderMap, Proxy};
+use crate::parameters::Parameters;
+use serde_json::Value;
+use crate::{ApiConfig, MidtransError, RequestOptions, RetryPolicy};
+use super::{MidtransClient, Transactions, Transport};
+use super::transport::ReqwestTransport;
+use crate::request_options::IdempotencyKeyGenerator;
+use crate::config::{ApiConfigBuilder, Environment, Operation};
+use crate::endpoint::Endpoint;
+use crate::http_client::to_parameters;
+use crate::models::{from_response, ChargeRequest, ChargeResponse, SubscriptionResponse, PayAccountResponse, PaymentLinkRequest, PaymentLinkResponse, InvoiceRequest, InvoiceResponse, CardBinResponse, CardRegisterResponse, CardTokenResponse, PointInquiryResponse, SubscriptionUpdateResponse};
+
+type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
+
+/// Async CoreApi struct used to do non-blocking request to Midtrans Core API
+pub struct CoreApi {
+    pub api_config: ApiConfig,
+    transport: Arc<dyn Transport>
+}
+
+impl MidtransClient for CoreApi {
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        Some(self.transport.as_ref())
+    }
+
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.api_config.get_retry_policy().as_ref()
+    }
+
+    fn get_timeout(&self, operation: Operation) -> Option<Duration> {
+        self.api_config.get_timeout(operation)
+    }
+}
+
+impl Transactions for CoreApi {
+    /// Getter for ApiConfig
+    fn get_api_config(&self) -> &ApiConfig {
+        &self.api_config
+    }
+
+    /// Setter for ApiConfig
+    fn set_api_config(&mut self, api_config: ApiConfig) {
+        self.api_config = api_config
+    }
+}
+pub struct CoreApiBuilder {
+    api_config: ApiConfigBuilder,
+    transport: Option<Arc<dyn Transport>>
+}
+
+impl CoreApiBuilder {
+    /// Override the environment chosen by `is_production`, e.g. `Environment::Custom`
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.api_config.environment(environment);
+        self
+    }
+
+    pub fn client_key(&mut self, client_key: String) -> &mut Self {
+        self.api_config.client_key(client_key);
+        self
+    }
+
+    pub fn custom_headers(&mut self, custom_headers: HeaderMap) -> &mut Self {
+        self.api_config.custom_header(custom_headers);
+        self
+    }
+
+    pub fn proxies(&mut self, proxies: Proxy) -> &mut Self {
+        self.api_config.proxies(proxies);
+        self
+    }
+
+    /// Retry transient failures of safe requests, see [`RetryPolicy`]
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.api_config.retry_policy(retry_policy);
+        self
+    }
+
+    /// Generate `Idempotency-Key` for requests that accept [`crate::RequestOptions`]
+    /// when none is given, e.g. [`crate::request_options::generate_idempotency_key`]
+    pub fn idempotency_key_generator(&mut self, generator: IdempotencyKeyGenerator) -> &mut Self {
+        self.api_config.idempotency_key_generator(generator);
+        self
+    }
+
+    /// Timeout for establishing the connection to Midtrans
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.connect_timeout(timeout);
+        self
+    }
+
+    /// Timeout of the whole request, exceeding it returns `MidtransError::TimeoutError`
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.timeout(timeout);
+        self
+    }
+
+    /// Override `timeout` for specific [`Operation`]
+    pub fn operation_timeout(&mut self, operation: Operation, timeout: Duration) -> &mut Self {
+        self.api_config.operation_timeout(operation, timeout);
+        self
+    }
+
+    /// Use custom [`Transport`] instead of the default `reqwest` one
+    pub fn transport(&mut self, transport: Arc<dyn Transport>) -> &mut Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Use preconfigured `reqwest::Client` for every request of this instance.
+    pub fn http_client(&mut self, http_client: reqwest::Client) -> &mut Self {
+        self.transport = Some(Arc::new(ReqwestTransport::from(http_client)));
+        self
+    }
+
+    pub fn build(&self) -> Result<CoreApi, MidtransError> {
+        let api_config = self.api_config.build();
+
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::from_config(&api_config)?)
+        };
+
+        Ok(CoreApi { api_config, transport })
+    }
+}
+
+impl CoreApi {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(is_production: bool, server_key: String) -> CoreApiBuilder {
+        CoreApiBuilder {
+            api_config: ApiConfig::new(is_production, server_key),
+            transport: None
+        }
+    }
+
+    /// Setter for custom [`Transport`]
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        self.transport = transport;
+    }
+
+    /// Trigger `/charge` API call to Core API
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
+    /// (more params detail refer to: <https://api-docs.midtrans.com>)
+    ///
+    pub async fn charge<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        self.charge_with_options(parameters, &RequestOptions::default()).await
+    }
+
+    /// Same as [`Self::charge`] with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    pub async fn charge_with_options<P: Parameters + ?Sized + Sync>(&self, parameters: &P, options: &RequestOptions) -> MidtransResult {
+        let custom_headers = options.merge_headers(
+            self.api_config.get_custom_headers(),
+            self.api_config.get_idempotency_key_generator()
+        )?;
+
+        let response = self.request(
+            Endpoint::charge(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            custom_headers,
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/charge` API call to Core API with typed parameters
+    ///
+    /// ### Argument
+    ///
+    /// `request` is a [`ChargeRequest`], serialized into Core API JSON
+    ///
+    pub async fn charge_typed(&self, request: &ChargeRequest) -> Result<ChargeResponse, MidtransError> {
+        from_response(self.charge(request).await?)
+    }
+
+    /// Trigger `/capture` API call to Core API
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
+    /// (more params detail refer to: <https://api-docs.midtrans.com>)
+    ///
+    pub async fn capture<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        self.capture_with_options(parameters, &RequestOptions::default()).await
+    }
+
+    /// Same as [`Self::capture`] with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    pub async fn capture_with_options<P: Parameters + ?Sized + Sync>(&self, parameters: &P, options: &RequestOptions) -> MidtransResult {
+        let custom_headers = options.merge_headers(
+            self.api_config.get_custom_headers(),
+            self.api_config.get_idempotency_key_generator()
+        )?;
+
+        let response = self.request(
+            Endpoint::capture(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            custom_headers,
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `capture` returning a typed [`ChargeResponse`]
+    pub async fn capture_typed<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<ChargeResponse, MidtransError> {
+        from_response(self.capture(parameters).await?)
+    }
+
+    /// Trigger `/card/register` API call to Core API
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON body as parameter
+    /// (more params detail refer to: <https://api-docs.midtrans.com>)
+    ///
+    pub async fn card_register<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::card_register(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `card_register` returning a typed [`CardRegisterResponse`]
+    pub async fn card_register_typed<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<CardRegisterResponse, MidtransError> {
+        from_response(self.card_register(parameters).await?)
+    }
+
+    /// Trigger `/token` API call to Core API
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON body as parameter
+    /// (more params detail refer to: <https://api-docs.midtrans.com>)
+    ///
+    pub async fn card_token<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::card_token(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `card_token` returning a typed [`CardTokenResponse`]
+    pub async fn card_token_typed<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<CardTokenResponse, MidtransError> {
+        from_response(self.card_token(parameters).await?)
+    }
+
+    /// Trigger `/point_inquiry/<token-id>` API call to Core API
+    ///
+    /// ### Argument
+    ///
+    /// `token_id` token id of credit card
+    /// (more params detail refer to: <https://api-docs.midtrans.com>)
+    ///
+    pub async fn card_point_inquiry(&self, token_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::card_point_inquiry(&self.api_config, token_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `card_point_inquiry` returning a typed [`PointInquiryResponse`]
+    pub async fn card_point_inquiry_typed(&self, token_id: &str) -> Result<PointInquiryResponse, MidtransError> {
+        from_response(self.card_point_inquiry(token_id).await?)
+    }
+
+    /// Trigger `/v1/bins/<bin>` API call to Core API, authenticated with the client key
+    ///
+    /// Get the issuing bank, brand and type of a card from its BIN
+    ///
+    /// ### Argument
+    ///
+    /// `bin` first 6 to 8 digits of the card number
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/bin-api>)
+    ///
+    pub async fn card_bin(&self, bin: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::card_bin(&self.api_config, bin)?,
+            self.api_config.required_client_key()?,
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `card_bin` returning a typed [`CardBinResponse`]
+    pub async fn card_bin_typed(&self, bin: &str) -> Result<CardBinResponse, MidtransError> {
+        from_response(self.card_bin(bin).await?)
+    }
+
+    /// Trigger `/v1/subscriptions` API call to Core API.
+    ///
+    /// Create a subscription transaction by sending all the details required to create a transaction
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#create-subscription>)
+    ///
+    pub async fn create_subscription<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::create_subscription(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `create_subscription` returning a typed [`SubscriptionResponse`]
+    pub async fn create_subscription_typed<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<SubscriptionResponse, MidtransError> {
+        from_response(self.create_subscription(parameters).await?)
+    }
+
+    /// Trigger `/v1/subscriptions/<subscription_id>` API call to Core API
+    ///
+    /// Retrieve the subscription details of a customer using the subscription_id
+    ///
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#get-subscription>)
+    ///
+    pub async fn get_subscription(&self, subscription_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::get_subscription(&self.api_config, subscription_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `get_subscription` returning a typed [`SubscriptionResponse`]
+    pub async fn get_subscription_typed(&self, subscription_id: &str) -> Result<SubscriptionResponse, MidtransError> {
+        from_response(self.get_subscription(subscription_id).await?)
+    }
+
+    /// Trigger `/v1/subscriptions/<subscription_id>/disable` API call to Core API
+    ///
+    /// Disable the customer's subscription. The customer will not be charged in the future for this subscription
+    ///
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#disable-subscription>)
+    ///
+    pub async fn disable_subscription(&self, subscription_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::disable_subscription(&self.api_config, subscription_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `disable_subscription` returning a typed [`SubscriptionUpdateResponse`]
+    pub async fn disable_subscription_typed(&self, subscription_id: &str) -> Result<SubscriptionUpdateResponse, MidtransError> {
+        from_response(self.disable_subscription(subscription_id).await?)
+    }
+
+    /// Trigger `/v1/subscriptions/<subscription_id>/enable` API call to Core API
+    ///
+    /// Enable the customer's subscription.
+    ///
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#enable-subscription>)
+    ///
+    pub async fn enable_subscription(&self, subscription_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::enable_subscription(&self.api_config, subscription_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `enable_subscription` returning a typed [`SubscriptionUpdateResponse`]
+    pub async fn enable_subscription_typed(&self, subscription_id: &str) -> Result<SubscriptionUpdateResponse, MidtransError> {
+        from_response(self.enable_subscription(subscription_id).await?)
+    }
+
+    /// Trigger `/v1/subscriptions/<subscription_id>` API call to Core API
+    ///
+    /// Update existing subscription details
+    ///
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#update-subscription>)
+    ///
+    pub async fn update_subscription<P: Parameters + ?Sized + Sync>(&self, subscription_id: &str, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::update_subscription(&self.api_config, subscription_id),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `update_subscription` returning a typed [`SubscriptionUpdateResponse`]
+    pub async fn update_subscription_typed<P: Parameters + ?Sized + Sync>(&self, subscription_id: &str, parameters: &P) -> Result<SubscriptionUpdateResponse, MidtransError> {
+        from_response(self.update_subscription(subscription_id, parameters).await?)
+    }
+
+    /// Trigger `/v2/pay/account` API call to Core API
+    ///
+    /// Link the customer account to be used for specific payment channels.
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#create-pay-account>)
+    ///
+    pub async fn link_payment_account<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::link_payment_account(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `link_payment_account` returning a typed [`PayAccountResponse`]
+    pub async fn link_payment_account_typed<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<PayAccountResponse, MidtransError> {
+        from_response(self.link_payment_account(parameters).await?)
+    }
+
+    /// Trigger `/v2/pay/account/<account-id>` API call to Core API
+    ///
+    /// Retrieve the payment account details of a customer using the account_id
+    ///
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#get-pay-account>)
+    ///
+    pub async fn get_payment_account(&self, account_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::get_payment_account(&self.api_config, account_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `get_payment_account` returning a typed [`PayAccountResponse`]
+    pub async fn get_payment_account_typed(&self, account_id: &str) -> Result<PayAccountResponse, MidtransError> {
+        from_response(self.get_payment_account(account_id).await?)
+    }
+
+    /// Trigger `/v2/pay/account/<account-id>/unbind` API call to Core API
+    ///
+    /// To remove the linked customer account
+    ///
+    /// (more params detail refer to: <https://api-docs.midtrans.com/#unbind-pay-account>)
+    ///
+    pub async fn unlink_payment_account(&self, account_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::unlink_payment_account(&self.api_config, account_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `unlink_payment_account` returning a typed [`PayAccountResponse`]
+    pub async fn unlink_payment_account_typed(&self, account_id: &str) -> Result<PayAccountResponse, MidtransError> {
+        from_response(self.unlink_payment_account(account_id).await?)
+    }
+
+    /// Trigger `/v1/payment-links` API call to Core API
+    ///
+    /// Create a shareable payment link
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/create-payment-link>)
+    ///
+    pub async fn create_payment_link<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::create_payment_link(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `create_payment_link` with a typed [`PaymentLinkRequest`] returning a typed [`PaymentLinkResponse`]
+    pub async fn create_payment_link_typed(&self, request: &PaymentLinkRequest) -> Result<PaymentLinkResponse, MidtransError> {
+        from_response(self.create_payment_link(request).await?)
+    }
+
+    /// Trigger `/v1/payment-links/<order_id>` API call to Core API
+    ///
+    /// Get details of a payment link
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/get-payment-link>)
+    ///
+    pub async fn get_payment_link(&self, order_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::get_payment_link(&self.api_config, order_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `get_payment_link` returning a typed [`PaymentLinkResponse`]
+    pub async fn get_payment_link_typed(&self, order_id: &str) -> Result<PaymentLinkResponse, MidtransError> {
+        from_response(self.get_payment_link(order_id).await?)
+    }
+
+    /// Trigger `/v1/payment-links/<order_id>` API call to Core API
+    ///
+    /// Delete a payment link so it can't be paid anymore
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/delete-payment-link>)
+    ///
+    pub async fn delete_payment_link(&self, order_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::delete_payment_link(&self.api_config, order_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/v1/invoices` API call to Core API
+    ///
+    /// Create an invoice paid with a payment link or virtual account
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/create-invoice>)
+    ///
+    pub async fn create_invoice<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::create_invoice(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `create_invoice` with a typed [`InvoiceRequest`] returning a typed [`InvoiceResponse`]
+    pub async fn create_invoice_typed(&self, request: &InvoiceRequest) -> Result<InvoiceResponse, MidtransError> {
+        from_response(self.create_invoice(request).await?)
+    }
+
+    /// Trigger `/v1/invoices/<invoice_id>` API call to Core API
+    ///
+    /// Get details of an invoice
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/get-invoice>)
+    ///
+    pub async fn get_invoice(&self, invoice_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::get_invoice(&self.api_config, invoice_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `get_invoice` returning a typed [`InvoiceResponse`]
+    pub async fn get_invoice_typed(&self, invoice_id: &str) -> Result<InvoiceResponse, MidtransError> {
+        from_response(self.get_invoice(invoice_id).await?)
+    }
+
+    /// Trigger `/v1/invoices/<invoice_id>/void` API call to Core API
+    ///
+    /// Void an unpaid invoice
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/void-invoice>)
+    ///
+    pub async fn void_invoice(&self, invoice_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::void_invoice(&self.api_config, invoice_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(not(feature = "mock-server"))]
+    use std::env;
+    use serde_json::json;
+
+    #[cfg(not(feature = "mock-server"))]
+    fn server_key() -> String {
+        env::var("MIDTRANS_SERVER_KEY").expect("SERVER_KEY NOT FOUND")
+    }
+
+    #[cfg(feature = "mock-server")]
+    fn server_key() -> String {
+        crate::mock_server::TEST_SERVER_KEY.to_string()
+    }
+
+    /// Sandbox instance, or one backed by the shared mock with the `mock-server` feature
+    fn generate_core_api_instance() -> Result<CoreApi, MidtransError> {
+        let core = &mut CoreApi::new(false, server_key());
+        #[cfg(feature = "mock-server")]
+        core.transport(crate::mock_server::shared());
+        core.build()
+    }
+
+    fn generate_order_id(test_number: u8) -> String {
+        let now = chrono::offset::Local::now().format("%Y%m%d%H%M%S").to_string();
+        // tests share one mock, keep their order ids apart within the same second
+        #[cfg(feature = "mock-server")]
+        let now = format!("{}-{}", now, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        format!("rust-midtransclient-async-test{}-{}", test_number, now)
+    }
+
+    #[test]
+    fn new() -> Result<(), MidtransError> {
+        let core = CoreApi::new(false, "server_key".to_string()).build()?;
+        assert!(!core.api_config.get_is_production());
+        assert_eq!(core.api_config.get_server_key(), "server_key");
+        assert_eq!(core.api_config.get_client_key(), "");
+        assert!(core.api_config.get_custom_headers().is_none());
+        assert!(core.api_config.get_proxies().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn futures_are_send() -> Result<(), MidtransError> {
+        fn assert_send<T: Send>(_: T) {}
+        let core = CoreApi::new(false, "server_key".to_string()).build()?;
+        assert_send(core.charge(""));
+        assert_send(core.status("order-id".to_string()));
+        assert_send(core.notification_from_str(""));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn charge_and_status() -> Result<(), MidtransError> {
+        let core = generate_core_api_instance()?;
+        let transaction_id = generate_order_id(1);
+        let parameters = json!({
+            "payment_type": "bank_transfer",
+            "transaction_details": {
+                "gross_amount": 25000,
+                "order_id": transaction_id
+            },
+            "bank_transfer": {
+                "bank": "bca"
+            }
+        }).to_string();
+        let _ = core.charge(&parameters).await?;
+        let response = core.status(transaction_id).await?;
+        assert_eq!(response["status_code"], "201");
+        assert_eq!(response["transaction_status"], "pending");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn status_fail_404() -> Result<(), MidtransError> {
+        let core = generate_core_api_instance()?;
+        let response = core.status("non-exist-order-id".to_string()).await;
+        assert!(response.is_err());
+        if let Err(MidtransError::ApiError(e)) = response {
+            assert_eq!(e.status_code, 404);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn card_bin_invalid() -> Result<(), MidtransError> {
+        let core = CoreApi::new(false, "server_key".to_string())
+            .client_key("client_key".to_string())
+            .build()?;
+        assert!(matches!(core.card_bin("4556-33").await, Err(MidtransError::InvalidParameters(_))));
+
+        let core = CoreApi::new(false, "server_key".to_string()).build()?;
+        assert!(matches!(core.card_bin("455633").await, Err(MidtransError::InvalidConfig(_))));
+        Ok(())
+    }
+}
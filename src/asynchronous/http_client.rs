@@ -0,0 +1,67 @@
+//! Async Http Client
+
+use std::collections::HashMap;
+use std::time::Duration;
+use async_trait::async_trait;
+use reqwest::{header::HeaderMap, Proxy};
+use serde_json::Value;
+use crate::{MidtransError, RetryPolicy, config::Operation, endpoint::Endpoint};
+use crate::http_client::{build_request, parse_response};
+use crate::retry::Retry;
+use super::transport::{Transport, ReqwestTransport};
+
+type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
+
+/// Non-blocking counterpart of [`crate::MidtransClient`]
+#[async_trait]
+pub trait MidtransClient: Sync {
+    /// Custom [`Transport`] used to send requests.
+    /// `None` means the default [`ReqwestTransport`] is used.
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        None
+    }
+
+    /// [`RetryPolicy`] applied to transient failures.
+    /// `None` means every request is only attempted once.
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        None
+    }
+
+    /// Timeout of the whole request for `operation`.
+    /// `None` means no timeout.
+    fn get_timeout(&self, _operation: Operation) -> Option<Duration> {
+        None
+    }
+
+    async fn request(
+        &self,
+        endpoint: Endpoint,
+        server_key: &str,
+        parameters: &Value,
+        custom_headers: Option<HeaderMap>,
+        proxies: Option<Proxy>
+    ) -> MidtransResult {
+        let operation = endpoint.operation;
+        let mut request = build_request(endpoint, server_key, parameters, custom_headers)?;
+        request.timeout = self.get_timeout(operation);
+
+        let mut retry = Retry::new(self.get_retry_policy());
+        loop {
+            let response = match self.get_transport() {
+                Some(transport) => transport.send(request.clone()).await,
+                None => ReqwestTransport::new(proxies.clone())?.send(request.clone()).await
+            }.and_then(|response| {
+                parse_response(response.status_code, &response.headers, &response.body)
+            });
+
+            let err = match response {
+                Err(err) => err,
+                response => return response
+            };
+            match retry.next_delay(&request, &err) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(err)
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Async (non-blocking) Midtrans clients
+//!
+//! Available with the `async` cargo feature. The API mirrors the blocking
+//! [`crate::CoreApi`], [`crate::Snap`] and [`crate::Transactions`], but every
+//! method returns a future that can be awaited on a tokio runtime.
+//!
+//! ```no_run
+//! use midtransclient::MidtransError;
+//! use midtransclient::asynchronous::{CoreApi, Transactions};
+//!
+//! # async fn run() -> Result<(), MidtransError> {
+//! let core = CoreApi::new(false, "YOUR_SERVER_KEY".to_string())
+//!     .client_key("YOUR_CLIENT_KEY".to_string())
+//!     .build()?;
+//!
+//! let response = core.status("order-id-123".to_string()).await?;
+//! println!("{:#?}", response);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod http_client;
+pub mod core_api;
+pub mod snap;
+pub mod transactions;
+pub mod transport;
+
+#[doc(inline)]
+pub use http_client::MidtransClient;
+#[doc(inline)]
+pub use core_api::CoreApi;
+#[doc(inline)]
+pub use snap::Snap;
+#[doc(inline)]
+pub use transactions::Transactions;
+#[doc(inline)]
+pub use transport::Transport;
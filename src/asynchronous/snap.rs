@@ -0,0 +1,247 @@
+//! Async Snap
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{header::HeaderMap, Proxy};
+use crate::parameters::Parameters;
+use serde_json::Value;
+use crate::{ApiConfig, MidtransError, RequestOptions, RetryPolicy};
+use super::{MidtransClient, Transactions, Transport};
+use super::transport::ReqwestTransport;
+use crate::config::{ApiConfigBuilder, Environment, Operation};
+use crate::endpoint::Endpoint;
+use crate::http_client::to_parameters;
+use crate::models::{from_response, SnapTransaction};
+
+type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
+
+/// Async Snap struct used to do non-blocking request to Midtrans Snap API
+pub struct Snap {
+    pub api_config: ApiConfig,
+    transport: Arc<dyn Transport>
+}
+
+impl MidtransClient for Snap {
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        Some(self.transport.as_ref())
+    }
+
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.api_config.get_retry_policy().as_ref()
+    }
+
+    fn get_timeout(&self, operation: Operation) -> Option<Duration> {
+        self.api_config.get_timeout(operation)
+    }
+}
+
+impl Transactions for Snap {
+    /// Getter for ApiConfig
+    fn get_api_config(&self) -> &ApiConfig {
+        &self.api_config
+    }
+
+    /// Setter for ApiConfig
+    fn set_api_config(&mut self, api_config: ApiConfig) {
+        self.api_config = api_config
+    }
+}
+
+pub struct SnapBuilder {
+    api_config: ApiConfigBuilder,
+    transport: Option<Arc<dyn Transport>>
+}
+
+impl SnapBuilder {
+    /// Override the environment chosen by `is_production`, e.g. `Environment::Custom`
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.api_config.environment(environment);
+        self
+    }
+
+    pub fn client_key(&mut self, client_key: String) -> &mut Self {
+        self.api_config.client_key(client_key);
+        self
+    }
+
+    pub fn custom_headers(&mut self, custom_headers: HeaderMap) -> &mut Self {
+        self.api_config.custom_header(custom_headers);
+        self
+    }
+
+    pub fn proxies(&mut self, proxies: Proxy) -> &mut Self {
+        self.api_config.proxies(proxies);
+        self
+    }
+
+    /// Retry transient failures of safe requests, see [`RetryPolicy`]
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.api_config.retry_policy(retry_policy);
+        self
+    }
+
+    /// Timeout for establishing the connection to Midtrans
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.connect_timeout(timeout);
+        self
+    }
+
+    /// Timeout of the whole request, exceeding it returns `MidtransError::TimeoutError`
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.timeout(timeout);
+        self
+    }
+
+    /// Override `timeout` for specific [`Operation`]
+    pub fn operation_timeout(&mut self, operation: Operation, timeout: Duration) -> &mut Self {
+        self.api_config.operation_timeout(operation, timeout);
+        self
+    }
+
+    /// Use custom [`Transport`] instead of the default `reqwest` one
+    pub fn transport(&mut self, transport: Arc<dyn Transport>) -> &mut Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Use preconfigured `reqwest::Client` for every request of this instance.
+    pub fn http_client(&mut self, http_client: reqwest::Client) -> &mut Self {
+        self.transport = Some(Arc::new(ReqwestTransport::from(http_client)));
+        self
+    }
+
+    pub fn build(&self) -> Result<Snap, MidtransError> {
+        let api_config = self.api_config.build();
+
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::from_config(&api_config)?)
+        };
+
+        Ok(Snap { api_config, transport })
+    }
+}
+
+impl Snap {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(is_production: bool, server_key: String) -> SnapBuilder {
+        SnapBuilder {
+            api_config: ApiConfig::new(is_production, server_key),
+            transport: None
+        }
+    }
+
+    /// Setter for custom [`Transport`]
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        self.transport = transport;
+    }
+
+    /// Trigger API call to Snap API
+    ///
+    /// ### Argument
+    ///
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
+    /// (more params detail refer to: <https://snap-docs.midtrans.com>)
+    ///
+    /// ### Return
+    ///
+    /// HashMap from JSON decoded response, that contains `token` and `redirect_url`
+    ///
+    pub async fn create_transaction<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> MidtransResult {
+        self.create_transaction_with_options(parameters, &RequestOptions::default()).await
+    }
+
+    /// Same as [`Self::create_transaction`] with per-request [`RequestOptions`],
+    /// e.g. `X-Override-Notification` / `X-Append-Notification` urls
+    pub async fn create_transaction_with_options<P: Parameters + ?Sized + Sync>(&self, parameters: &P, options: &RequestOptions) -> MidtransResult {
+        let custom_headers = options.merge_headers(
+            self.api_config.get_custom_headers(),
+            None
+        )?;
+
+        let response = self.request(
+            Endpoint::snap_transaction(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            custom_headers,
+            self.api_config.get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `create_transaction` returning a typed [`SnapTransaction`]
+    pub async fn create_transaction_typed<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<SnapTransaction, MidtransError> {
+        from_response(self.create_transaction(parameters).await?)
+    }
+
+    /// Wrapper method that call `create_transaction` and directly return `token`
+    pub async fn create_transaction_token<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<Value, MidtransError> {
+        let response = self.create_transaction(parameters).await?;
+        Ok(response["token"].clone())
+    }
+
+    /// Wrapper method that call `create_transaction` and directly return `redirect_url`
+    pub async fn create_transaction_redirect_url<P: Parameters + ?Sized + Sync>(&self, parameters: &P) -> Result<Value, MidtransError> {
+        let response = self.create_transaction(parameters).await?;
+        Ok(response["redirect_url"].clone())
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(not(feature = "mock-server"))]
+    use std::env;
+    use serde_json::json;
+
+    #[cfg(not(feature = "mock-server"))]
+    fn server_key() -> String {
+        env::var("MIDTRANS_SERVER_KEY").expect("SERVER_KEY NOT FOUND")
+    }
+
+    #[cfg(feature = "mock-server")]
+    fn server_key() -> String {
+        crate::mock_server::TEST_SERVER_KEY.to_string()
+    }
+
+    /// Sandbox instance, or one backed by the shared mock with the `mock-server` feature
+    fn generate_snap_api_instance() -> Result<Snap, MidtransError> {
+        let snap = &mut Snap::new(false, server_key());
+        #[cfg(feature = "mock-server")]
+        snap.transport(crate::mock_server::shared());
+        snap.build()
+    }
+
+    #[test]
+    fn new() {
+        let snap = Snap::new(false, "server_key".to_string()).build().unwrap();
+        assert!(!snap.api_config.get_is_production());
+        assert_eq!(snap.api_config.get_server_key(), "server_key");
+        assert_eq!(snap.api_config.get_client_key(), "");
+        assert!(snap.api_config.get_custom_headers().is_none());
+        assert!(snap.api_config.get_proxies().is_none());
+    }
+
+    #[tokio::test]
+    async fn create_transaction_min() -> Result<(), MidtransError> {
+        let snap = generate_snap_api_instance()?;
+        let now = chrono::offset::Local::now().format("%Y%m%d%H%M%S").to_string();
+        #[cfg(feature = "mock-server")]
+        let now = format!("{}-{}", now, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let parameters = json!({
+            "transaction_details": {
+                "order_id": format!("rust-midtransclient-async-test1-{}", now),
+                "gross_amount": 200000
+            }, "credit_card":{
+                "secure" : true
+            }
+        }).to_string();
+        let transaction = snap.create_transaction(&parameters).await?;
+        assert!(transaction.contains_key("token"));
+        assert!(transaction.contains_key("redirect_url"));
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+//! Async Transactions
+
+use std::collections::HashMap;
+use async_trait::async_trait;
+use crate::parameters::Parameters;
+use serde_json::Value;
+use crate::signature;
+use crate::endpoint::Endpoint;
+use crate::transactions::notification_transaction_id;
+use crate::http_client::to_parameters;
+use crate::models::{from_response, TransactionStatusResponse, TransactionStatusB2bResponse, RefundResponse};
+use crate::{MidtransError, ApiConfig, RequestOptions};
+use super::MidtransClient;
+
+type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
+
+/// Non-blocking counterpart of [`crate::Transactions`]
+#[async_trait]
+pub trait Transactions: MidtransClient {
+    fn get_api_config(&self) -> &ApiConfig;
+
+    fn set_api_config(&mut self, api_config: ApiConfig);
+
+    async fn status(&self, transaction_id: String) -> MidtransResult {
+        let response = self.request(
+            Endpoint::status(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &Value::Null,
+            self.get_api_config().get_custom_headers().clone(),
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn statusb2b(&self, transaction_id: String) -> MidtransResult {
+        let response = self.request(
+            Endpoint::status_b2b(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &Value::Null,
+            self.get_api_config().get_custom_headers().clone(),
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn approve(&self, transaction_id: String) -> MidtransResult {
+        let response = self.request(
+            Endpoint::approve(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &Value::Null,
+            self.get_api_config().get_custom_headers().clone(),
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn deny(&self, transaction_id: String) -> MidtransResult {
+        let response = self.request(
+            Endpoint::deny(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &Value::Null,
+            self.get_api_config().get_custom_headers().clone(),
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn cancel(&self, transaction_id: String) -> MidtransResult {
+        let response = self.request(
+            Endpoint::cancel(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &Value::Null,
+            self.get_api_config().get_custom_headers().clone(),
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn expire(&self, transaction_id: String) -> MidtransResult {
+        let response = self.request(
+            Endpoint::expire(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &Value::Null,
+            self.get_api_config().get_custom_headers().clone(),
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn refund<P: Parameters + ?Sized + Sync>(&self, transaction_id: String, parameters: &P) -> MidtransResult where Self: Sized {
+        self.refund_with_options(transaction_id, parameters, &RequestOptions::default()).await
+    }
+
+    /// `refund` with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    async fn refund_with_options<P: Parameters + ?Sized + Sync>(&self, transaction_id: String, parameters: &P, options: &RequestOptions) -> MidtransResult where Self: Sized {
+        let custom_headers = options.merge_headers(
+            self.get_api_config().get_custom_headers(),
+            self.get_api_config().get_idempotency_key_generator()
+        )?;
+
+        let response = self.request(
+            Endpoint::refund(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &to_parameters(parameters)?,
+            custom_headers,
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    async fn refund_direct<P: Parameters + ?Sized + Sync>(&self, transaction_id: String, parameters: &P) -> MidtransResult where Self: Sized {
+        self.refund_direct_with_options(transaction_id, parameters, &RequestOptions::default()).await
+    }
+
+    /// `refund_direct` with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    async fn refund_direct_with_options<P: Parameters + ?Sized + Sync>(&self, transaction_id: String, parameters: &P, options: &RequestOptions) -> MidtransResult where Self: Sized {
+        let custom_headers = options.merge_headers(
+            self.get_api_config().get_custom_headers(),
+            self.get_api_config().get_idempotency_key_generator()
+        )?;
+
+        let response = self.request(
+            Endpoint::refund_direct(self.get_api_config(), &transaction_id),
+            self.get_api_config().get_server_key(),
+            &to_parameters(parameters)?,
+            custom_headers,
+            self.get_api_config().get_proxies().clone()
+        ).await?;
+
+        Ok(response)
+    }
+
+    /// `status` returning a typed [`TransactionStatusResponse`]
+    async fn status_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.status(transaction_id).await?)
+    }
+
+    /// `statusb2b` returning a typed [`TransactionStatusB2bResponse`]
+    async fn statusb2b_typed(&self, transaction_id: String) -> Result<TransactionStatusB2bResponse, MidtransError> {
+        from_response(self.statusb2b(transaction_id).await?)
+    }
+
+    /// `approve` returning a typed [`TransactionStatusResponse`]
+    async fn approve_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.approve(transaction_id).await?)
+    }
+
+    /// `deny` returning a typed [`TransactionStatusResponse`]
+    async fn deny_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.deny(transaction_id).await?)
+    }
+
+    /// `cancel` returning a typed [`TransactionStatusResponse`]
+    async fn cancel_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.cancel(transaction_id).await?)
+    }
+
+    /// `expire` returning a typed [`TransactionStatusResponse`]
+    async fn expire_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.expire(transaction_id).await?)
+    }
+
+    /// `refund` returning a typed [`RefundResponse`]
+    async fn refund_typed<P: Parameters + ?Sized + Sync>(&self, transaction_id: String, parameters: &P) -> Result<RefundResponse, MidtransError> where Self: Sized {
+        from_response(self.refund(transaction_id, parameters).await?)
+    }
+
+    /// `refund_direct` returning a typed [`RefundResponse`]
+    async fn refund_direct_typed<P: Parameters + ?Sized + Sync>(&self, transaction_id: String, parameters: &P) -> Result<RefundResponse, MidtransError> where Self: Sized {
+        from_response(self.refund_direct(transaction_id, parameters).await?)
+    }
+
+    /// Verify the `signature_key` of a notification with the server key of `ApiConfig`,
+    /// without any request to Midtrans
+    fn verify_signature(&self, notification: &HashMap<String, Value>) -> Result<(), MidtransError> {
+        signature::verify_notification(notification, self.get_api_config().get_server_key())
+    }
+
+    /// Get the status of the transaction a notification refers to, by its
+    /// `transaction_id` or `order_id` when `transaction_id` is absent or null
+    async fn notification_from_json(&self, notification: HashMap<String, Value>) -> MidtransResult {
+        let transaction_id = notification_transaction_id(&notification)?;
+        self.status(transaction_id).await
+    }
+
+    async fn notification_from_str(&self, notification: &str) -> MidtransResult {
+        let notification: HashMap<String, Value> = serde_json::from_str(notification)?;
+        self.notification_from_json(notification).await
+    }
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+//! Async Http Transport
+
+use async_trait::async_trait;
+use reqwest::Proxy;
+use crate::{ApiConfig, MidtransError, http_client::HttpClient};
+use crate::transport::{TransportRequest, TransportResponse};
+
+/// Non-blocking counterpart of [`crate::Transport`]
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError>;
+}
+
+/// Default async [`Transport`] backed by `reqwest::Client`
+pub struct ReqwestTransport {
+    client: reqwest::Client
+}
+
+impl ReqwestTransport {
+    pub fn new(proxies: Option<Proxy>) -> Result<Self, MidtransError> {
+        let mut client = HttpClient::new();
+        if let Some(proxies) = proxies {
+            client.proxies(proxies);
+        }
+        Ok(Self { client: client.build_async()? })
+    }
+
+    /// Build transport with proxies and connect timeout of `api_config`
+    pub fn from_config(api_config: &ApiConfig) -> Result<Self, MidtransError> {
+        let mut client = HttpClient::new();
+        if let Some(proxies) = api_config.get_proxies() {
+            client.proxies(proxies.clone());
+        }
+        if let Some(timeout) = api_config.get_connect_timeout() {
+            client.connect_timeout(timeout);
+        }
+        Ok(Self { client: client.build_async()? })
+    }
+}
+
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        let mut request_builder = self.client
+            .request(request.method, request.url)
+            .headers(request.headers);
+
+        if let Some(body) = request.body {
+            request_builder = request_builder.body(body);
+        }
+
+        if let Some(timeout) = request.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder.send().await?;
+        let headers = response.headers().clone();
+        let status_code = response.status().as_u16();
+        let body = response.text().await?;
+
+        Ok(TransportResponse { status_code, headers, body })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::asynchronous::{CoreApi, Transactions};
+    use crate::transport::FakeTransport;
+
+    #[tokio::test]
+    async fn status_uses_custom_transport() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200", "transaction_status": "settlement"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        let response = core.status("order-id".to_string()).await?;
+        assert_eq!(response["transaction_status"], "settlement");
+        assert_eq!(transport.last_request().url, "https://api.sandbox.midtrans.com/v2/order-id/status");
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+//! Record and Replay
+//!
+//! [`CassetteTransport`] records request/response pairs sent through another
+//! [`Transport`] into a JSON cassette file, and replays them later without
+//! network access, so integration tests against the sandbox become deterministic.
+//!
+//! The server key, the `Authorization` header and card data (`card_number`,
+//! `card_cvv`, `card_exp_month`, `card_exp_year`) are scrubbed before anything
+//! is written.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use midtransclient::{CoreApi, MidtransError};
+//! use midtransclient::cassette::CassetteTransport;
+//! use midtransclient::transport::ReqwestTransport;
+//!
+//! # fn main() -> Result<(), MidtransError> {
+//! // record once against the sandbox
+//! let transport = CassetteTransport::record("tests/cassettes/charge.json", Arc::new(ReqwestTransport::new(None)?));
+//! // then replay
+//! let transport = CassetteTransport::replay("tests/cassettes/charge.json")?;
+//!
+//! let core = CoreApi::new(false, "YOUR_SERVER_KEY".to_string())
+//!     .transport(Arc::new(transport))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::MidtransError;
+use crate::transport::{Transport, TransportRequest, TransportResponse};
+
+const SCRUBBED: &str = "[SCRUBBED]";
+const CARD_FIELDS: [&str; 4] = ["card_number", "card_cvv", "card_exp_month", "card_exp_year"];
+
+/// Recorded request, already scrubbed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<Value>
+}
+
+/// Recorded response, already scrubbed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status_code: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse
+}
+
+/// Content of a cassette file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>
+}
+
+impl Cassette {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MidtransError> {
+        let content = fs::read_to_string(path).map_err(|e| MidtransError::TransportError(Box::new(e)))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MidtransError> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).map_err(|e| MidtransError::TransportError(Box::new(e)))
+    }
+}
+
+enum Mode {
+    Record(Arc<dyn Transport>),
+    /// Replay, removing each interaction once served
+    Replay
+}
+
+/// [`Transport`] recording to or replaying from a cassette file
+pub struct CassetteTransport {
+    path: PathBuf,
+    mode: Mode,
+    cassette: Mutex<Cassette>
+}
+
+impl CassetteTransport {
+    /// Send requests through `inner` and record them into a new cassette at `path`,
+    /// the file is rewritten after every interaction
+    pub fn record<P: AsRef<Path>>(path: P, inner: Arc<dyn Transport>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Record(inner),
+            cassette: Mutex::new(Cassette::default())
+        }
+    }
+
+    /// Serve the interactions recorded at `path`
+    ///
+    /// A request matches an interaction with the same method, url and body.
+    /// Each interaction is served once, in recorded order, and a request without
+    /// a matching interaction fails with `MidtransError::TransportError`.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self, MidtransError> {
+        let cassette = Cassette::load(&path)?;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            mode: Mode::Replay,
+            cassette: Mutex::new(cassette)
+        })
+    }
+
+    /// Whether every recorded interaction has been replayed
+    pub fn is_exhausted(&self) -> bool {
+        match &self.mode {
+            Mode::Record(_) => true,
+            Mode::Replay => self.cassette.lock().unwrap_or_else(|e| e.into_inner()).interactions.is_empty()
+        }
+    }
+}
+
+impl Transport for CassetteTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        let server_key = server_key(&request.headers);
+        let recorded_request = scrub_request(&request, server_key.as_deref());
+
+        match &self.mode {
+            Mode::Record(inner) => {
+                let response = inner.send(request)?;
+                let recorded_response = RecordedResponse {
+                    status_code: response.status_code,
+                    headers: headers_to_map(&response.headers, server_key.as_deref()),
+                    body: scrub_text(&response.body, server_key.as_deref())
+                };
+
+                let mut cassette = self.cassette.lock().unwrap_or_else(|e| e.into_inner());
+                cassette.interactions.push(Interaction { request: recorded_request, response: recorded_response });
+                cassette.save(&self.path)?;
+                Ok(response)
+            },
+            Mode::Replay => {
+                let mut cassette = self.cassette.lock().unwrap_or_else(|e| e.into_inner());
+                let position = cassette.interactions.iter().position(|interaction| {
+                    interaction.request.method == recorded_request.method
+                        && interaction.request.url == recorded_request.url
+                        && interaction.request.body == recorded_request.body
+                });
+                match position {
+                    Some(position) => {
+                        let interaction = cassette.interactions.remove(position);
+                        Ok(TransportResponse {
+                            status_code: interaction.response.status_code,
+                            headers: map_to_headers(&interaction.response.headers),
+                            body: interaction.response.body
+                        })
+                    },
+                    None => Err(MidtransError::TransportError(format!(
+                        "no recorded interaction in {} matches {} {}",
+                        self.path.display(),
+                        recorded_request.method,
+                        recorded_request.url
+                    ).into()))
+                }
+            }
+        }
+    }
+}
+
+/// Server key from the `Authorization: Basic base64(server_key:)` header
+fn server_key(headers: &HeaderMap) -> Option<String> {
+    let credentials = headers.get(header::AUTHORIZATION)?.to_str().ok()?.strip_prefix("Basic ")?;
+    let credentials = String::from_utf8(general_purpose::STANDARD.decode(credentials).ok()?).ok()?;
+    let server_key = credentials.trim_end_matches(':');
+    (!server_key.is_empty()).then(|| server_key.to_string())
+}
+
+fn scrub_request(request: &TransportRequest, server_key: Option<&str>) -> RecordedRequest {
+    let body = request.body.as_deref().map(|body| match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            scrub_value(&mut value, server_key);
+            value
+        },
+        Err(_) => Value::String(scrub_text(body, server_key))
+    });
+
+    RecordedRequest {
+        method: request.method.to_string(),
+        url: scrub_url(&request.url, server_key),
+        headers: headers_to_map(&request.headers, server_key),
+        body
+    }
+}
+
+fn scrub_url(url: &str, server_key: Option<&str>) -> String {
+    let mut url = match reqwest::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return scrub_text(url, server_key)
+    };
+    if url.query().is_some() {
+        // query parameters are sorted since their order is not stable between runs
+        let pairs: BTreeMap<String, String> = url.query_pairs()
+            .map(|(key, value)| match CARD_FIELDS.contains(&key.as_ref()) {
+                true => (key.into_owned(), SCRUBBED.to_string()),
+                false => (key.into_owned(), scrub_text(&value, server_key))
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    scrub_text(url.as_str(), server_key)
+}
+
+fn scrub_value(value: &mut Value, server_key: Option<&str>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match CARD_FIELDS.contains(&key.as_str()) {
+                    true => *value = Value::String(SCRUBBED.to_string()),
+                    false => scrub_value(value, server_key)
+                }
+            }
+        },
+        Value::Array(values) => values.iter_mut().for_each(|value| scrub_value(value, server_key)),
+        Value::String(text) => *text = scrub_text(text, server_key),
+        _ => {}
+    }
+}
+
+fn scrub_text(text: &str, server_key: Option<&str>) -> String {
+    match server_key {
+        Some(server_key) => text.replace(server_key, SCRUBBED),
+        None => text.to_string()
+    }
+}
+
+fn headers_to_map(headers: &HeaderMap, server_key: Option<&str>) -> BTreeMap<String, String> {
+    headers.iter()
+        .filter_map(|(name, value)| {
+            let value = match *name == header::AUTHORIZATION {
+                true => SCRUBBED.to_string(),
+                false => scrub_text(value.to_str().ok()?, server_key)
+            };
+            Some((name.to_string(), value))
+        })
+        .collect()
+}
+
+fn map_to_headers(map: &BTreeMap<String, String>) -> HeaderMap {
+    map.iter()
+        .filter_map(|(name, value)| Some((
+            HeaderName::from_bytes(name.as_bytes()).ok()?,
+            HeaderValue::from_str(value).ok()?
+        )))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use serde_json::json;
+    use crate::CoreApi;
+
+    const SERVER_KEY: &str = "SB-Mid-server-CASSETTE";
+
+    /// Answers every request with a card token
+    struct EchoTransport;
+
+    impl Transport for EchoTransport {
+        fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+            let body = json!({
+                "status_code": "200",
+                "method": request.method.to_string(),
+                "token_id": "481111-1114-token"
+            });
+            Ok(TransportResponse { status_code: 200, headers: HeaderMap::new(), body: body.to_string() })
+        }
+    }
+
+    fn cassette_path() -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("midtrans-cassette-{}.json", nanos))
+    }
+
+    fn core_api(transport: CassetteTransport) -> CoreApi {
+        CoreApi::new(false, SERVER_KEY.to_string())
+            .transport(Arc::new(transport))
+            .build()
+            .unwrap()
+    }
+
+    const CARD: &str = r#"{
+        "card_number": "4811111111111114",
+        "card_exp_month": "12",
+        "card_exp_year": "2030",
+        "card_cvv": "123",
+        "client_key": "SB-Mid-client-CASSETTE"
+    }"#;
+
+    const CHARGE: &str = r#"{
+        "payment_type": "credit_card",
+        "transaction_details": {"order_id": "order-1", "gross_amount": 10000},
+        "credit_card": {"token_id": "481111-1114-token", "card_cvv": "123"}
+    }"#;
+
+    #[test]
+    fn record_scrubs_secrets() {
+        let path = cassette_path();
+        let core = core_api(CassetteTransport::record(&path, Arc::new(EchoTransport)));
+        core.card_token(CARD).unwrap();
+        core.charge(CHARGE).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains(SERVER_KEY));
+        assert!(!content.contains("4811111111111114"));
+        assert!(!content.contains("\"123\""));
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn replay_recorded_interactions() {
+        let path = cassette_path();
+        let core = core_api(CassetteTransport::record(&path, Arc::new(EchoTransport)));
+        let recorded_token = core.card_token(CARD).unwrap();
+        let recorded_charge = core.charge(CHARGE).unwrap();
+
+        let transport = CassetteTransport::replay(&path).unwrap();
+        let core = core_api(transport);
+        assert_eq!(core.charge(CHARGE).unwrap()["token_id"], recorded_charge["token_id"]);
+        assert_eq!(core.card_token(CARD).unwrap()["token_id"], recorded_token["token_id"]);
+
+        // every interaction is replayed once
+        let error = core.charge(CHARGE).unwrap_err();
+        assert!(matches!(error, MidtransError::TransportError(_)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn replay_fails_on_unmatched_request() {
+        let path = cassette_path();
+        let core = core_api(CassetteTransport::record(&path, Arc::new(EchoTransport)));
+        core.charge(CHARGE).unwrap();
+
+        let transport = CassetteTransport::replay(&path).unwrap();
+        let core = core_api(transport);
+        let other_charge = CHARGE.replace("order-1", "order-2");
+        assert!(matches!(core.charge(&other_charge), Err(MidtransError::TransportError(_))));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,12 +1,74 @@
 //! Api Config
 
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::time::Duration;
 use reqwest::header;
+use crate::{MidtransError, RetryPolicy};
+use crate::request_options::IdempotencyKeyGenerator;
 
-const CORE_SANDBOX_BASE_URL: &'static str = "https://api.sandbox.midtrans.com";
-const CORE_PRODUCTION_BASE_URL:  &'static str = "https://api.midtrans.com";
-const SNAP_SANDBOX_BASE_URL: &'static str = "https://app.sandbox.midtrans.com";
-const SNAP_PRODUCTION_BASE_URL: &'static str = "https://app.midtrans.com";
+const CORE_SANDBOX_BASE_URL: &str = "https://api.sandbox.midtrans.com";
+const CORE_PRODUCTION_BASE_URL:  &str = "https://api.midtrans.com";
+const SNAP_SANDBOX_BASE_URL: &str = "https://app.sandbox.midtrans.com";
+const SNAP_PRODUCTION_BASE_URL: &str = "https://app.midtrans.com";
+const IRIS_SANDBOX_BASE_URL: &str = "https://app.sandbox.midtrans.com/iris";
+const IRIS_PRODUCTION_BASE_URL: &str = "https://app.midtrans.com/iris";
+
+/// Midtrans environment that decides the API base urls.
+///
+/// `Custom` can be used to point the client to a mock server, an egress proxy host
+/// or a regional endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Environment {
+    Sandbox,
+    Production,
+    Custom {
+        core_url: String,
+        snap_url: String,
+        iris_url: String
+    }
+}
+
+/// Midtrans API operation, used to set per-operation timeout with
+/// [`ApiConfigBuilder::operation_timeout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Charge,
+    Capture,
+    CardRegister,
+    CardToken,
+    CardPointInquiry,
+    CardBin,
+    Subscription,
+    PaymentAccount,
+    PaymentLink,
+    Invoice,
+    SnapTransaction,
+    Status,
+    StatusB2b,
+    Approve,
+    Deny,
+    Cancel,
+    Expire,
+    Refund,
+    RefundDirect,
+    IrisBalance,
+    IrisBeneficiary,
+    IrisPayout,
+    IrisStatement,
+    IrisTopupChannel,
+    IrisBeneficiaryBank,
+    IrisAccountValidation,
+    Other
+}
+
+/// Base urls of [`Environment::Custom`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CustomUrls {
+    core_url: String,
+    snap_url: String,
+    iris_url: String
+}
 
 /// Config Object that used to store is_production, server_key, client_key.
 /// And also API base urls.
@@ -14,10 +76,16 @@ const SNAP_PRODUCTION_BASE_URL: &'static str = "https://app.midtrans.com";
 /// note: client_key is not necessarily required for API call.
 pub struct ApiConfig {
     pub is_production: bool,
+    custom_urls: Option<CustomUrls>,
     pub server_key: String,
     pub client_key: String,
     pub custom_headers: Option<header::HeaderMap>,
-    pub proxies: Option<reqwest::Proxy>
+    pub proxies: Option<reqwest::Proxy>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub idempotency_key_generator: Option<IdempotencyKeyGenerator>,
+    pub connect_timeout: Option<Duration>,
+    pub timeout: Option<Duration>,
+    pub operation_timeouts: HashMap<Operation, Duration>
 }
 
 impl Display for ApiConfig {
@@ -34,27 +102,44 @@ impl Display for ApiConfig {
 }
 
 impl ApiConfig {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(is_production: bool, server_key: String) -> ApiConfigBuilder {
         ApiConfigBuilder {
             is_production,
+            environment: None,
             server_key,
             client_key: None,
             custom_header: None,
-            proxies: None
+            proxies: None,
+            retry_policy: None,
+            idempotency_key_generator: None,
+            connect_timeout: None,
+            timeout: None,
+            operation_timeouts: HashMap::new()
+        }
+    }
+
+    pub fn get_core_api_base_url(&self) -> &str {
+        match (&self.custom_urls, self.is_production) {
+            (Some(urls), _) => urls.core_url.trim_end_matches('/'),
+            (_, true) => CORE_PRODUCTION_BASE_URL,
+            (_, false) => CORE_SANDBOX_BASE_URL
         }
     }
 
-    pub fn get_core_api_base_url(&self) -> &'static str {
-        match self.is_production {
-            true => CORE_PRODUCTION_BASE_URL,
-            false => CORE_SANDBOX_BASE_URL
+    pub fn get_snap_base_url(&self) -> &str {
+        match (&self.custom_urls, self.is_production) {
+            (Some(urls), _) => urls.snap_url.trim_end_matches('/'),
+            (_, true) => SNAP_PRODUCTION_BASE_URL,
+            (_, false) => SNAP_SANDBOX_BASE_URL
         }
     }
 
-    pub fn get_snap_base_url(&self) -> &'static str {
-        match self.is_production {
-            true => SNAP_PRODUCTION_BASE_URL,
-            false => SNAP_SANDBOX_BASE_URL
+    pub fn get_iris_base_url(&self) -> &str {
+        match (&self.custom_urls, self.is_production) {
+            (Some(urls), _) => urls.iris_url.trim_end_matches('/'),
+            (_, true) => IRIS_PRODUCTION_BASE_URL,
+            (_, false) => IRIS_SANDBOX_BASE_URL
         }
     }
 
@@ -62,10 +147,41 @@ impl ApiConfig {
         self.is_production
     }
 
+    /// Note: has no effect on base urls of `Environment::Custom`
     pub fn set_is_production(&mut self, value: bool) {
         self.is_production = value;
     }
 
+    /// `Environment::Custom` when custom base urls are set, otherwise chosen by `is_production`
+    pub fn get_environment(&self) -> Environment {
+        match (&self.custom_urls, self.is_production) {
+            (Some(urls), _) => Environment::Custom {
+                core_url: urls.core_url.clone(),
+                snap_url: urls.snap_url.clone(),
+                iris_url: urls.iris_url.clone()
+            },
+            (None, true) => Environment::Production,
+            (None, false) => Environment::Sandbox
+        }
+    }
+
+    /// Note: `Environment::Custom` keeps the current `is_production`
+    pub fn set_environment(&mut self, environment: Environment) {
+        self.custom_urls = match environment {
+            Environment::Production => {
+                self.is_production = true;
+                None
+            },
+            Environment::Sandbox => {
+                self.is_production = false;
+                None
+            },
+            Environment::Custom { core_url, snap_url, iris_url } => {
+                Some(CustomUrls { core_url, snap_url, iris_url })
+            }
+        };
+    }
+
     pub fn get_server_key(&self) -> &str {
         &self.server_key
     }
@@ -78,6 +194,14 @@ impl ApiConfig {
         &self.client_key
     }
 
+    /// Client key of the requests authenticated with it, fails when none is configured
+    pub(crate) fn required_client_key(&self) -> Result<&str, MidtransError> {
+        match self.client_key.is_empty() {
+            true => Err(MidtransError::InvalidConfig("client key is required".to_string())),
+            false => Ok(&self.client_key)
+        }
+    }
+
     pub fn set_client_key(&mut self, value: String) {
         self.client_key = value;
     }
@@ -94,20 +218,72 @@ impl ApiConfig {
         &self.proxies
     }
 
+    /// Note: `CoreApi` and `Snap` build their HTTP client once, so proxies
+    /// changed after `build()` are not applied to their requests.
     pub fn set_proxies(&mut self, proxies: reqwest::Proxy) {
         self.proxies = Some(proxies);
     }
+
+    pub fn get_retry_policy(&self) -> &Option<RetryPolicy> {
+        &self.retry_policy
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = Some(retry_policy);
+    }
+
+    pub fn get_idempotency_key_generator(&self) -> Option<IdempotencyKeyGenerator> {
+        self.idempotency_key_generator
+    }
+
+    pub fn set_idempotency_key_generator(&mut self, generator: IdempotencyKeyGenerator) {
+        self.idempotency_key_generator = Some(generator);
+    }
+
+    pub fn get_connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Note: like proxies, connect timeout is applied when `CoreApi` / `Snap` is built.
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.connect_timeout = Some(timeout);
+    }
+
+    /// Timeout of the whole request, unless overridden for `operation`
+    pub fn get_timeout(&self, operation: Operation) -> Option<Duration> {
+        self.operation_timeouts.get(&operation).copied().or(self.timeout)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    pub fn set_operation_timeout(&mut self, operation: Operation, timeout: Duration) {
+        self.operation_timeouts.insert(operation, timeout);
+    }
 }
 
 pub struct ApiConfigBuilder {
     is_production: bool,
+    environment: Option<Environment>,
     server_key: String,
     client_key: Option<String>,
     custom_header: Option<header::HeaderMap>,
-    proxies: Option<reqwest::Proxy>
+    proxies: Option<reqwest::Proxy>,
+    retry_policy: Option<RetryPolicy>,
+    idempotency_key_generator: Option<IdempotencyKeyGenerator>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    operation_timeouts: HashMap<Operation, Duration>
 }
 
 impl ApiConfigBuilder {
+    /// Override the environment chosen by `is_production`, e.g. `Environment::Custom`
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.environment = Some(environment);
+        self
+    }
+
     pub fn client_key(&mut self, client_key: String) -> &mut Self {
         self.client_key = Some(client_key);
         self
@@ -123,30 +299,73 @@ impl ApiConfigBuilder {
         self
     }
 
-    pub fn build(&mut self) -> ApiConfig {
-        ApiConfig {
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    pub fn idempotency_key_generator(&mut self, generator: IdempotencyKeyGenerator) -> &mut Self {
+        self.idempotency_key_generator = Some(generator);
+        self
+    }
+
+    /// Timeout for establishing the connection to Midtrans
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout of the whole request, from connecting until the response body is read
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Override `timeout` for specific [`Operation`]
+    pub fn operation_timeout(&mut self, operation: Operation, timeout: Duration) -> &mut Self {
+        self.operation_timeouts.insert(operation, timeout);
+        self
+    }
+
+    pub fn build(&self) -> ApiConfig {
+        let mut api_config = ApiConfig {
             is_production: self.is_production,
+            custom_urls: None,
             server_key: self.server_key.clone(),
-            client_key: self.client_key.clone().unwrap_or(String::new()),
+            client_key: self.client_key.clone().unwrap_or_default(),
             custom_headers: self.custom_header.clone(),
-            proxies: self.proxies.clone()
+            proxies: self.proxies.clone(),
+            retry_policy: self.retry_policy.clone(),
+            idempotency_key_generator: self.idempotency_key_generator,
+            connect_timeout: self.connect_timeout,
+            timeout: self.timeout,
+            operation_timeouts: self.operation_timeouts.clone()
+        };
+        if let Some(environment) = &self.environment {
+            api_config.set_environment(environment.clone());
         }
+        api_config
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{CoreApi, Snap, Transactions};
+    use crate::transport::FakeTransport;
 
     #[test]
     fn build() {
         let server_key = String::from("secret_key");
         let config = ApiConfig::new(false, server_key).build();
-        assert_eq!(config.is_production, false);
+        assert!(!config.is_production);
         assert_eq!(config.server_key, "secret_key");
         assert_eq!(config.client_key, "");
         assert!(config.custom_headers.is_none());
         assert!(config.proxies.is_none());
+        assert!(config.retry_policy.is_none());
+        assert!(config.connect_timeout.is_none());
+        assert!(config.timeout.is_none());
     }
 
     #[test]
@@ -174,11 +393,59 @@ mod test {
         assert_eq!(config.get_snap_base_url(), SNAP_PRODUCTION_BASE_URL);
     }
 
+    #[test]
+    fn get_iris_base_url() {
+        let server_key = String::from("secret_key");
+        let mut config = ApiConfig::new(false, server_key).build();
+        assert_eq!(config.get_iris_base_url(), IRIS_SANDBOX_BASE_URL);
+        config.set_is_production(true);
+        assert_eq!(config.get_iris_base_url(), IRIS_PRODUCTION_BASE_URL);
+    }
+
+    #[test]
+    fn custom_environment() {
+        let server_key = String::from("secret_key");
+        let environment = Environment::Custom {
+            core_url: "http://localhost:8080/".to_string(),
+            snap_url: "http://localhost:8081".to_string(),
+            iris_url: "http://localhost:8082/iris".to_string()
+        };
+        let mut config = ApiConfig::new(false, server_key)
+            .environment(environment.clone())
+            .build();
+        assert_eq!(config.get_core_api_base_url(), "http://localhost:8080");
+        assert_eq!(config.get_snap_base_url(), "http://localhost:8081");
+        assert_eq!(config.get_iris_base_url(), "http://localhost:8082/iris");
+        config.set_is_production(true);
+        assert_eq!(config.get_environment(), environment);
+        assert_eq!(config.get_core_api_base_url(), "http://localhost:8080");
+    }
+
+    #[test]
+    fn set_environment() {
+        let server_key = String::from("secret_key");
+        let mut config = ApiConfig::new(false, server_key).build();
+        assert_eq!(config.get_environment(), Environment::Sandbox);
+        config.set_environment(Environment::Production);
+        assert!(config.get_is_production());
+        assert_eq!(config.get_core_api_base_url(), CORE_PRODUCTION_BASE_URL);
+        config.is_production = false;
+        assert_eq!(config.get_environment(), Environment::Sandbox);
+        config.set_environment(Environment::Custom {
+            core_url: "http://localhost:8080".to_string(),
+            snap_url: "http://localhost:8081".to_string(),
+            iris_url: "http://localhost:8082/iris".to_string()
+        });
+        assert!(!config.get_is_production());
+        config.set_environment(Environment::Production);
+        assert_eq!(config.get_core_api_base_url(), CORE_PRODUCTION_BASE_URL);
+    }
+
     #[test]
     fn get_is_production() {
         let server_key = String::from("secret_key");
         let config = ApiConfig::new(false, server_key).build();
-        assert_eq!(config.get_is_production(), false);
+        assert!(!config.get_is_production());
     }
 
     #[test]
@@ -186,7 +453,7 @@ mod test {
         let server_key = String::from("secret_key");
         let mut config = ApiConfig::new(false, server_key).build();
         config.set_is_production(true);
-        assert_eq!(config.get_is_production(), true);
+        assert!(config.get_is_production());
     }
 
     #[test]
@@ -250,4 +517,86 @@ mod test {
         let proxies = reqwest::Proxy::http("https://secure.example").unwrap();
         config.set_proxies(proxies.clone());
     }
+
+    #[test]
+    fn get_retry_policy() {
+        let server_key = String::from("secret_key");
+        let config = ApiConfig::new(false, server_key).build();
+        assert!(config.get_retry_policy().is_none());
+    }
+
+    #[test]
+    fn set_retry_policy() {
+        let server_key = String::from("secret_key");
+        let mut config = ApiConfig::new(false, server_key).build();
+        config.set_retry_policy(RetryPolicy::new(5).build());
+        assert_eq!(config.get_retry_policy().as_ref().unwrap().max_attempts, 5);
+    }
+
+    #[test]
+    fn get_idempotency_key_generator() {
+        let server_key = String::from("secret_key");
+        let config = ApiConfig::new(false, server_key).build();
+        assert!(config.get_idempotency_key_generator().is_none());
+    }
+
+    #[test]
+    fn set_idempotency_key_generator() {
+        let server_key = String::from("secret_key");
+        let mut config = ApiConfig::new(false, server_key).build();
+        config.set_idempotency_key_generator(|| "key".to_string());
+        assert_eq!(config.get_idempotency_key_generator().unwrap()(), "key");
+    }
+
+    #[test]
+    fn get_timeout() {
+        let server_key = String::from("secret_key");
+        let mut config = ApiConfig::new(false, server_key)
+            .timeout(Duration::from_secs(10))
+            .operation_timeout(Operation::Charge, Duration::from_secs(30))
+            .build();
+        assert_eq!(config.get_timeout(Operation::Charge), Some(Duration::from_secs(30)));
+        assert_eq!(config.get_timeout(Operation::Status), Some(Duration::from_secs(10)));
+        config.set_operation_timeout(Operation::Status, Duration::from_secs(2));
+        assert_eq!(config.get_timeout(Operation::Status), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn operation_timeout_is_set_on_request() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .timeout(Duration::from_secs(10))
+            .operation_timeout(Operation::Charge, Duration::from_secs(30))
+            .transport(transport.clone())
+            .build()?;
+        core.charge(r#"{"payment_type": "gopay"}"#)?;
+        assert_eq!(transport.last_request().timeout, Some(Duration::from_secs(30)));
+        core.status("order-id".to_string())?;
+        assert_eq!(transport.last_request().timeout, Some(Duration::from_secs(10)));
+        Ok(())
+    }
+
+    #[test]
+    fn custom_environment_base_urls() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200", "token": "abc"}"#);
+        let environment = Environment::Custom {
+            core_url: "http://localhost:8080".to_string(),
+            snap_url: "http://localhost:8081".to_string(),
+            iris_url: "http://localhost:8082".to_string()
+        };
+        let core = CoreApi::new(true, "server_key".to_string())
+            .environment(environment.clone())
+            .transport(transport.clone())
+            .build()?;
+        core.status("order-id".to_string())?;
+        assert_eq!(transport.last_request().url, "http://localhost:8080/v2/order-id/status");
+
+        let snap = Snap::new(true, "server_key".to_string())
+            .environment(environment)
+            .transport(transport.clone())
+            .build()?;
+        snap.create_transaction(r#"{"transaction_details": {}}"#)?;
+        assert_eq!(transport.last_request().url, "http://localhost:8081/snap/v1/transactions");
+        Ok(())
+    }
 }
@@ -1,18 +1,39 @@
 //! CoreApi
 
 use std::collections::HashMap;
-use reqwest::{self, Method, header::HeaderMap, Proxy};
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{self, header::HeaderMap, Proxy};
+use crate::parameters::Parameters;
 use serde_json::Value;
-use crate::{ApiConfig, MidtransError, Transactions, http_client::MidtransClient};
+use crate::{ApiConfig, MidtransError, RequestOptions, RetryPolicy, Transactions, Transport, http_client::{MidtransClient, to_parameters}};
+use crate::transport::ReqwestTransport;
+use crate::request_options::IdempotencyKeyGenerator;
+use crate::config::{ApiConfigBuilder, Environment, Operation};
+use crate::endpoint::Endpoint;
+use crate::models::{from_response, ChargeRequest, ChargeResponse, SubscriptionResponse, PayAccountResponse, PaymentLinkRequest, PaymentLinkResponse, InvoiceRequest, InvoiceResponse, CardBinResponse, CardRegisterResponse, CardTokenResponse, PointInquiryResponse, SubscriptionUpdateResponse};
 
 type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
 
 /// CoreApi struct used to do request to Midtrans Core API
 pub struct CoreApi {
     pub api_config: ApiConfig,
+    transport: Arc<dyn Transport>
 }
 
-impl MidtransClient for CoreApi {}
+impl MidtransClient for CoreApi {
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        Some(self.transport.as_ref())
+    }
+
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.api_config.get_retry_policy().as_ref()
+    }
+
+    fn get_timeout(&self, operation: Operation) -> Option<Duration> {
+        self.api_config.get_timeout(operation)
+    }
+}
 
 impl Transactions for CoreApi {
     /// Getter for ApiConfig
@@ -26,129 +47,186 @@ impl Transactions for CoreApi {
     }
 }
 pub struct CoreApiBuilder {
-    is_production: bool,
-    server_key: String,
-    client_key: Option<String>,
-    custom_headers: Option<HeaderMap>,
-    proxies: Option<Proxy>
+    api_config: ApiConfigBuilder,
+    transport: Option<Arc<dyn Transport>>
 }
 
 impl CoreApiBuilder {
+    /// Override the environment chosen by `is_production`, e.g. `Environment::Custom`
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.api_config.environment(environment);
+        self
+    }
+
     pub fn client_key(&mut self, client_key: String) -> &mut Self {
-        self.client_key = Some(client_key);
+        self.api_config.client_key(client_key);
         self
     }
 
     pub fn custom_headers(&mut self, custom_headers: HeaderMap) -> &mut Self {
-        self.custom_headers = Some(custom_headers);
+        self.api_config.custom_header(custom_headers);
         self
     }
 
     pub fn proxies(&mut self, proxies: Proxy) -> &mut Self {
-        self.proxies = Some(proxies);
+        self.api_config.proxies(proxies);
         self
     }
 
-    pub fn build(&self) -> Result<CoreApi, MidtransError> {
-        let mut api_config = ApiConfig::new(self.is_production, self.server_key.clone());
+    /// Retry transient failures of safe requests, see [`RetryPolicy`]
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.api_config.retry_policy(retry_policy);
+        self
+    }
 
-        if let Some(key) = &self.client_key {
-            api_config.client_key(key.clone());
-        }
+    /// Generate `Idempotency-Key` for requests that accept [`crate::RequestOptions`]
+    /// when none is given, e.g. [`crate::request_options::generate_idempotency_key`]
+    pub fn idempotency_key_generator(&mut self, generator: IdempotencyKeyGenerator) -> &mut Self {
+        self.api_config.idempotency_key_generator(generator);
+        self
+    }
 
-        if let Some(headers) = &self.custom_headers {
-            api_config.custom_header(headers.clone());
-        }
+    /// Timeout for establishing the connection to Midtrans
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.connect_timeout(timeout);
+        self
+    }
 
-        if let Some(proxy) = &self.proxies {
-            api_config.proxies(proxy.clone());
-        }
+    /// Timeout of the whole request, exceeding it returns `MidtransError::TimeoutError`
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.timeout(timeout);
+        self
+    }
+
+    /// Override `timeout` for specific [`Operation`]
+    pub fn operation_timeout(&mut self, operation: Operation, timeout: Duration) -> &mut Self {
+        self.api_config.operation_timeout(operation, timeout);
+        self
+    }
+
+    /// Use custom [`Transport`] instead of the default `reqwest` one
+    pub fn transport(&mut self, transport: Arc<dyn Transport>) -> &mut Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Use preconfigured `reqwest::blocking::Client` for every request of this instance.
+    pub fn http_client(&mut self, http_client: reqwest::blocking::Client) -> &mut Self {
+        self.transport = Some(Arc::new(ReqwestTransport::from(http_client)));
+        self
+    }
 
-        let api_config = api_config.build();
+    pub fn build(&self) -> Result<CoreApi, MidtransError> {
+        let api_config = self.api_config.build();
+
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::from_config(&api_config)?)
+        };
 
-        Ok(CoreApi { api_config })
+        Ok(CoreApi { api_config, transport })
     }
 }
 
 impl CoreApi {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(is_production: bool, server_key: String) -> CoreApiBuilder {
         CoreApiBuilder {
-            is_production,
-            server_key,
-            client_key: None,
-            custom_headers: None,
-            proxies: None
+            api_config: ApiConfig::new(is_production, server_key),
+            transport: None
         }
     }
 
+    /// Setter for custom [`Transport`]
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        self.transport = transport;
+    }
+
     /// Trigger `/charge` API call to Core API
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
     /// (more params detail refer to: <https://api-docs.midtrans.com>)
     ///
-    pub fn charge(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/charge",
-            self.api_config.get_core_api_base_url()
-        );
+    pub fn charge<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        self.charge_with_options(parameters, &RequestOptions::default())
+    }
+
+    /// Same as [`Self::charge`] with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    pub fn charge_with_options<P: Parameters + ?Sized>(&self, parameters: &P, options: &RequestOptions) -> MidtransResult {
+        let custom_headers = options.merge_headers(
+            self.api_config.get_custom_headers(),
+            self.api_config.get_idempotency_key_generator()
+        )?;
 
         let response = self.request(
-            Method::POST,
+            Endpoint::charge(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
-            self.api_config.get_custom_headers().clone(),
+            &to_parameters(parameters)?,
+            custom_headers,
             self.api_config.get_proxies().clone()
         )?;
 
         Ok(response)
     }
 
+    /// Trigger `/charge` API call to Core API with typed parameters
+    ///
+    /// ### Argument
+    ///
+    /// `request` is a [`ChargeRequest`], serialized into Core API JSON
+    ///
+    pub fn charge_typed(&self, request: &ChargeRequest) -> Result<ChargeResponse, MidtransError> {
+        from_response(self.charge(request)?)
+    }
+
     /// Trigger `/capture` API call to Core API
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
     /// (more params detail refer to: <https://api-docs.midtrans.com>)
     ///
-    pub fn capture(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/capture",
-            self.api_config.get_core_api_base_url()
-        );
+    pub fn capture<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        self.capture_with_options(parameters, &RequestOptions::default())
+    }
+
+    /// Same as [`Self::capture`] with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    pub fn capture_with_options<P: Parameters + ?Sized>(&self, parameters: &P, options: &RequestOptions) -> MidtransResult {
+        let custom_headers = options.merge_headers(
+            self.api_config.get_custom_headers(),
+            self.api_config.get_idempotency_key_generator()
+        )?;
 
         let response = self.request(
-            Method::POST,
+            Endpoint::capture(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
-            self.api_config.get_custom_headers().clone(),
+            &to_parameters(parameters)?,
+            custom_headers,
             self.api_config.get_proxies().clone()
         )?;
 
         Ok(response)
     }
 
+    /// `capture` returning a typed [`ChargeResponse`]
+    pub fn capture_typed<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<ChargeResponse, MidtransError> {
+        from_response(self.capture(parameters)?)
+    }
+
     /// Trigger `/card/register` API call to Core API
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON body as parameter
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON body as parameter
     /// (more params detail refer to: <https://api-docs.midtrans.com>)
     ///
-    pub fn card_register(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/card/register",
-            self.api_config.get_core_api_base_url()
-        );
-
+    pub fn card_register<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
         let response = self.request(
-            Method::GET,
+            Endpoint::card_register(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
+            &to_parameters(parameters)?,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -156,24 +234,23 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `card_register` returning a typed [`CardRegisterResponse`]
+    pub fn card_register_typed<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<CardRegisterResponse, MidtransError> {
+        from_response(self.card_register(parameters)?)
+    }
+
     /// Trigger `/token` API call to Core API
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON body as parameter
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON body as parameter
     /// (more params detail refer to: <https://api-docs.midtrans.com>)
     ///
-    pub fn card_token(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/token",
-            self.api_config.get_core_api_base_url()
-        );
-
+    pub fn card_token<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
         let response = self.request(
-            Method::GET,
+            Endpoint::card_token(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
+            &to_parameters(parameters)?,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -181,6 +258,11 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `card_token` returning a typed [`CardTokenResponse`]
+    pub fn card_token_typed<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<CardTokenResponse, MidtransError> {
+        from_response(self.card_token(parameters)?)
+    }
+
     /// Trigger `/point_inquiry/<token-id>` API call to Core API
     ///
     /// ### Argument
@@ -189,17 +271,36 @@ impl CoreApi {
     /// (more params detail refer to: <https://api-docs.midtrans.com>)
     ///
     pub fn card_point_inquiry(&self, token_id: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/point_inquiry/{}",
-            self.api_config.get_core_api_base_url(),
-            token_id
-        );
-
         let response = self.request(
-            Method::GET,
+            Endpoint::card_point_inquiry(&self.api_config, token_id),
             self.api_config.get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// `card_point_inquiry` returning a typed [`PointInquiryResponse`]
+    pub fn card_point_inquiry_typed(&self, token_id: &str) -> Result<PointInquiryResponse, MidtransError> {
+        from_response(self.card_point_inquiry(token_id)?)
+    }
+
+    /// Trigger `/v1/bins/<bin>` API call to Core API, authenticated with the client key
+    ///
+    /// Get the issuing bank, brand and type of a card from its BIN
+    ///
+    /// ### Argument
+    ///
+    /// `bin` first 6 to 8 digits of the card number
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/bin-api>)
+    ///
+    pub fn card_bin(&self, bin: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::card_bin(&self.api_config, bin)?,
+            self.api_config.required_client_key()?,
+            &Value::Null,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -207,26 +308,25 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `card_bin` returning a typed [`CardBinResponse`]
+    pub fn card_bin_typed(&self, bin: &str) -> Result<CardBinResponse, MidtransError> {
+        from_response(self.card_bin(bin)?)
+    }
+
     /// Trigger `/v1/subscriptions` API call to Core API.
     ///
     /// Create a subscription transaction by sending all the details required to create a transaction
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
     /// (more params detail refer to: <https://api-docs.midtrans.com/#create-subscription>)
     ///
-    pub fn create_subscription(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v1/subscriptions",
-            self.api_config.get_core_api_base_url()
-        );
-
+    pub fn create_subscription<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
         let response = self.request(
-            Method::POST,
+            Endpoint::create_subscription(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
+            &to_parameters(parameters)?,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -234,6 +334,11 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `create_subscription` returning a typed [`SubscriptionResponse`]
+    pub fn create_subscription_typed<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<SubscriptionResponse, MidtransError> {
+        from_response(self.create_subscription(parameters)?)
+    }
+
     /// Trigger `/v1/subscriptions/<subscription_id>` API call to Core API
     ///
     /// Retrieve the subscription details of a customer using the subscription_id
@@ -241,17 +346,10 @@ impl CoreApi {
     /// (more params detail refer to: <https://api-docs.midtrans.com/#get-subscription>)
     ///
     pub fn get_subscription(&self, subscription_id: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v1/subscriptions/{}",
-            self.api_config.get_core_api_base_url(),
-            subscription_id
-        );
-
         let response = self.request(
-            Method::GET,
+            Endpoint::get_subscription(&self.api_config, subscription_id),
             self.api_config.get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -259,6 +357,11 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `get_subscription` returning a typed [`SubscriptionResponse`]
+    pub fn get_subscription_typed(&self, subscription_id: &str) -> Result<SubscriptionResponse, MidtransError> {
+        from_response(self.get_subscription(subscription_id)?)
+    }
+
     /// Trigger `/v1/subscriptions/<subscription_id>/disable` API call to Core API
     ///
     /// Disable the customer's subscription. The customer will not be charged in the future for this subscription
@@ -266,17 +369,10 @@ impl CoreApi {
     /// (more params detail refer to: <https://api-docs.midtrans.com/#disable-subscription>)
     ///
     pub fn disable_subscription(&self, subscription_id: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v1/subscriptions/{}/disable",
-            self.api_config.get_core_api_base_url(),
-            subscription_id
-        );
-
         let response = self.request(
-            Method::POST,
+            Endpoint::disable_subscription(&self.api_config, subscription_id),
             self.api_config.get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -284,6 +380,11 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `disable_subscription` returning a typed [`SubscriptionUpdateResponse`]
+    pub fn disable_subscription_typed(&self, subscription_id: &str) -> Result<SubscriptionUpdateResponse, MidtransError> {
+        from_response(self.disable_subscription(subscription_id)?)
+    }
+
     /// Trigger `/v1/subscriptions/<subscription_id>/enable` API call to Core API
     ///
     /// Enable the customer's subscription.
@@ -291,17 +392,10 @@ impl CoreApi {
     /// (more params detail refer to: <https://api-docs.midtrans.com/#enable-subscription>)
     ///
     pub fn enable_subscription(&self, subscription_id: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v1/subscriptions/{}/enable",
-            self.api_config.get_core_api_base_url(),
-            subscription_id
-        );
-
         let response = self.request(
-            Method::POST,
+            Endpoint::enable_subscription(&self.api_config, subscription_id),
             self.api_config.get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -309,24 +403,22 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `enable_subscription` returning a typed [`SubscriptionUpdateResponse`]
+    pub fn enable_subscription_typed(&self, subscription_id: &str) -> Result<SubscriptionUpdateResponse, MidtransError> {
+        from_response(self.enable_subscription(subscription_id)?)
+    }
+
     /// Trigger `/v1/subscriptions/<subscription_id>` API call to Core API
     ///
     /// Update existing subscription details
     ///
     /// (more params detail refer to: <https://api-docs.midtrans.com/#update-subscription>)
     ///
-    pub fn update_subscription(&self, subscription_id: &str, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v1/subscriptions/{}",
-            self.api_config.get_core_api_base_url(),
-            subscription_id
-        );
-
+    pub fn update_subscription<P: Parameters + ?Sized>(&self, subscription_id: &str, parameters: &P) -> MidtransResult {
         let response = self.request(
-            Method::PATCH,
+            Endpoint::update_subscription(&self.api_config, subscription_id),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
+            &to_parameters(parameters)?,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -334,26 +426,25 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `update_subscription` returning a typed [`SubscriptionUpdateResponse`]
+    pub fn update_subscription_typed<P: Parameters + ?Sized>(&self, subscription_id: &str, parameters: &P) -> Result<SubscriptionUpdateResponse, MidtransError> {
+        from_response(self.update_subscription(subscription_id, parameters)?)
+    }
+
     /// Trigger `/v2/pay/account` API call to Core API
     ///
     /// Link the customer account to be used for specific payment channels.
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
     /// (more params detail refer to: <https://api-docs.midtrans.com/#create-pay-account>)
     ///
-    pub fn link_payment_account(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/pay/account",
-            self.api_config.get_core_api_base_url()
-        );
-
+    pub fn link_payment_account<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
         let response = self.request(
-            Method::POST,
+            Endpoint::link_payment_account(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
+            &to_parameters(parameters)?,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -361,6 +452,11 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `link_payment_account` returning a typed [`PayAccountResponse`]
+    pub fn link_payment_account_typed<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<PayAccountResponse, MidtransError> {
+        from_response(self.link_payment_account(parameters)?)
+    }
+
     /// Trigger `/v2/pay/account/<account-id>` API call to Core API
     ///
     /// Retrieve the payment account details of a customer using the account_id
@@ -368,17 +464,10 @@ impl CoreApi {
     /// (more params detail refer to: <https://api-docs.midtrans.com/#get-pay-account>)
     ///
     pub fn get_payment_account(&self, account_id: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/pay/account/{}",
-            self.api_config.get_core_api_base_url(),
-            account_id
-        );
-
         let response = self.request(
-            Method::GET,
+            Endpoint::get_payment_account(&self.api_config, account_id),
             self.api_config.get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -386,6 +475,11 @@ impl CoreApi {
         Ok(response)
     }
 
+    /// `get_payment_account` returning a typed [`PayAccountResponse`]
+    pub fn get_payment_account_typed(&self, account_id: &str) -> Result<PayAccountResponse, MidtransError> {
+        from_response(self.get_payment_account(account_id)?)
+    }
+
     /// Trigger `/v2/pay/account/<account-id>/unbind` API call to Core API
     ///
     /// To remove the linked customer account
@@ -393,17 +487,143 @@ impl CoreApi {
     /// (more params detail refer to: <https://api-docs.midtrans.com/#unbind-pay-account>)
     ///
     pub fn unlink_payment_account(&self, account_id: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/pay/account/{}/unbind",
-            self.api_config.get_core_api_base_url(),
-            account_id
-        );
+        let response = self.request(
+            Endpoint::unlink_payment_account(&self.api_config, account_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// `unlink_payment_account` returning a typed [`PayAccountResponse`]
+    pub fn unlink_payment_account_typed(&self, account_id: &str) -> Result<PayAccountResponse, MidtransError> {
+        from_response(self.unlink_payment_account(account_id)?)
+    }
 
+    /// Trigger `/v1/payment-links` API call to Core API
+    ///
+    /// Create a shareable payment link
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/create-payment-link>)
+    ///
+    pub fn create_payment_link<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
         let response = self.request(
-            Method::POST,
+            Endpoint::create_payment_link(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            "",
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// `create_payment_link` with a typed [`PaymentLinkRequest`] returning a typed [`PaymentLinkResponse`]
+    pub fn create_payment_link_typed(&self, request: &PaymentLinkRequest) -> Result<PaymentLinkResponse, MidtransError> {
+        from_response(self.create_payment_link(request)?)
+    }
+
+    /// Trigger `/v1/payment-links/<order_id>` API call to Core API
+    ///
+    /// Get details of a payment link
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/get-payment-link>)
+    ///
+    pub fn get_payment_link(&self, order_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::get_payment_link(&self.api_config, order_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// `get_payment_link` returning a typed [`PaymentLinkResponse`]
+    pub fn get_payment_link_typed(&self, order_id: &str) -> Result<PaymentLinkResponse, MidtransError> {
+        from_response(self.get_payment_link(order_id)?)
+    }
+
+    /// Trigger `/v1/payment-links/<order_id>` API call to Core API
+    ///
+    /// Delete a payment link so it can't be paid anymore
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/delete-payment-link>)
+    ///
+    pub fn delete_payment_link(&self, order_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::delete_payment_link(&self.api_config, order_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/v1/invoices` API call to Core API
+    ///
+    /// Create an invoice paid with a payment link or virtual account
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/create-invoice>)
+    ///
+    pub fn create_invoice<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::create_invoice(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// `create_invoice` with a typed [`InvoiceRequest`] returning a typed [`InvoiceResponse`]
+    pub fn create_invoice_typed(&self, request: &InvoiceRequest) -> Result<InvoiceResponse, MidtransError> {
+        from_response(self.create_invoice(request)?)
+    }
+
+    /// Trigger `/v1/invoices/<invoice_id>` API call to Core API
+    ///
+    /// Get details of an invoice
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/get-invoice>)
+    ///
+    pub fn get_invoice(&self, invoice_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::get_invoice(&self.api_config, invoice_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// `get_invoice` returning a typed [`InvoiceResponse`]
+    pub fn get_invoice_typed(&self, invoice_id: &str) -> Result<InvoiceResponse, MidtransError> {
+        from_response(self.get_invoice(invoice_id)?)
+    }
+
+    /// Trigger `/v1/invoices/<invoice_id>/void` API call to Core API
+    ///
+    /// Void an unpaid invoice
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/void-invoice>)
+    ///
+    pub fn void_invoice(&self, invoice_id: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::void_invoice(&self.api_config, invoice_id),
+            self.api_config.get_server_key(),
+            &Value::Null,
             self.api_config.get_custom_headers().clone(),
             self.api_config.get_proxies().clone()
         )?;
@@ -436,23 +656,47 @@ mod test {
 
     mod helper {
         use super::*;
+        #[cfg(not(feature = "mock-server"))]
         use std::env;
         use chrono;
 
+        #[cfg(not(feature = "mock-server"))]
         pub(super) fn server_key() -> String {
             env::var("MIDTRANS_SERVER_KEY").expect("SERVER_KEY NOT FOUND")
         }
 
+        #[cfg(not(feature = "mock-server"))]
         pub(super) fn client_key() -> String {
             env::var("MIDTRANS_CLIENT_KEY").expect("CLIENT_KEY NOT FOUND")
         }
 
+        #[cfg(feature = "mock-server")]
+        pub(super) fn server_key() -> String {
+            crate::mock_server::TEST_SERVER_KEY.to_string()
+        }
+
+        #[cfg(feature = "mock-server")]
+        pub(super) fn client_key() -> String {
+            crate::mock_server::TEST_CLIENT_KEY.to_string()
+        }
+
+        /// Sandbox instance, or one backed by the shared mock with the `mock-server` feature
+        pub(super) fn new_core_api(server_key: String) -> CoreApi {
+            let core = &mut CoreApi::new(false, server_key);
+            #[cfg(feature = "mock-server")]
+            core.transport(crate::mock_server::shared());
+            core.build().unwrap()
+        }
+
         pub(super) fn generate_core_api_instance() -> CoreApi {
-            CoreApi::new(false, server_key()).build().unwrap()
+            new_core_api(server_key())
         }
 
         pub(super) fn generate_order_id(test_number: u8) -> String {
             let now = chrono::offset::Local::now().format("%Y%m%d%H%M%S").to_string();
+            // tests share one mock, keep their order ids apart within the same second
+            #[cfg(feature = "mock-server")]
+            let now = format!("{}-{}", now, &uuid::Uuid::new_v4().simple().to_string()[..8]);
             format!("rust-midtransclient-test{}-{}", test_number, now)
         }
 
@@ -568,7 +812,7 @@ mod test {
         #[test]
         fn new() -> Result<(), MidtransError> {
             let core = CoreApi::new(false, "server_key".to_string()).build()?;
-            assert_eq!(core.api_config.get_is_production(), false);
+            assert!(!core.api_config.get_is_production());
             assert_eq!(core.api_config.get_server_key(), "server_key");
             assert_eq!(core.api_config.get_client_key(), "");
             assert!(core.api_config.get_custom_headers().is_none());
@@ -589,7 +833,7 @@ mod test {
                 .custom_headers(custom_headers.clone())
                 .proxies(proxies)
                 .build()?;
-            assert_eq!(core.api_config.get_is_production(), false);
+            assert!(!core.api_config.get_is_production());
             assert_eq!(core.api_config.get_server_key(), "server_key");
             assert_eq!(core.api_config.get_client_key(), "client_key");
             assert_eq!(core.api_config.get_custom_headers().clone(), Some(custom_headers));
@@ -597,6 +841,55 @@ mod test {
             Ok(())
         }
 
+        #[test]
+        fn new_with_http_client() -> Result<(), MidtransError> {
+            use std::io::{BufRead, BufReader, Write};
+            use std::net::TcpListener;
+            use std::thread;
+            use reqwest::header::HeaderValue;
+
+            let listener = TcpListener::bind("127.0.0.1:0").expect("bind local listener");
+            let url = format!("http://{}", listener.local_addr().expect("local address"));
+            let server = thread::spawn(move || {
+                let (stream, _) = listener.accept().expect("accept request");
+                let mut reader = BufReader::new(stream);
+                let mut head = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).expect("read request");
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+                let body = r#"{"status_code": "200", "order_id": "order-1"}"#;
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                ).expect("write response");
+                head
+            });
+
+            let mut default_headers = HeaderMap::new();
+            default_headers.insert("x-injected-client", HeaderValue::from_static("new_with_http_client"));
+            let http_client = reqwest::blocking::Client::builder()
+                .default_headers(default_headers)
+                .build()?;
+            let core = CoreApi::new(false, "server_key".to_string())
+                .environment(Environment::Custom { core_url: url.clone(), snap_url: url.clone(), iris_url: url })
+                .http_client(http_client)
+                .build()?;
+
+            let response = core.status("order-1".to_string())?;
+            assert_eq!(response["order_id"], "order-1");
+            let request = server.join().expect("server thread").to_lowercase();
+            assert!(request.starts_with("get /v2/order-1/status "), "{}", request);
+            assert!(request.contains("x-injected-client: new_with_http_client"), "{}", request);
+            Ok(())
+        }
+
         #[test]
         fn card_token() -> Result<(), MidtransError> {
             let core = generate_core_api_instance();
@@ -626,6 +919,41 @@ mod test {
             Ok(())
         }
 
+        #[test]
+        fn card_token_typed() -> Result<(), MidtransError> {
+            let transport = crate::transport::FakeTransport::new(200, r#"{"status_code": "200", "token_id": "481111-1114-token", "bank": "bni"}"#);
+            let core = CoreApi::new(false, "server_key".to_string())
+                .transport(transport)
+                .build()?;
+            let response = core.card_token_typed(r#"{"card_cvv": "123"}"#)?;
+            assert_eq!(response.token_id.as_deref(), Some("481111-1114-token"));
+            assert_eq!(response.bank.as_deref(), Some("bni"));
+            Ok(())
+        }
+
+        #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
+        fn card_bin() -> Result<(), MidtransError> {
+            let core = generate_core_api_instance();
+            let response = core.card_bin_typed("455633")?;
+            assert_eq!(response.data.bank_code.as_deref(), Some("BNI"));
+            Ok(())
+        }
+
+        #[test]
+        fn card_bin_invalid() -> Result<(), MidtransError> {
+            let core = CoreApi::new(false, "server_key".to_string())
+                .client_key("client_key".to_string())
+                .build()?;
+            for bin in ["45563", "455633123", "4556-33", ""] {
+                assert!(matches!(core.card_bin(bin), Err(MidtransError::InvalidParameters(_))), "{}", bin);
+            }
+
+            let core = CoreApi::new(false, "server_key".to_string()).build()?;
+            assert!(matches!(core.card_bin("455633"), Err(MidtransError::InvalidConfig(_))));
+            Ok(())
+        }
+
         #[test]
         fn charge_cc_simple() -> Result<(), MidtransError> {
             let core = generate_core_api_instance();
@@ -667,6 +995,20 @@ mod test {
             Ok(())
         }
 
+        #[test]
+        fn charge_typed_bank_transfer_bca() -> Result<(), MidtransError> {
+            use crate::models::{PaymentMethod, TransactionStatus, charge::{BankTransfer, Bank}};
+            let core = generate_core_api_instance();
+            let order_id = generate_order_id(1);
+            let bank_transfer = BankTransfer::new(Bank::Bca);
+            let request = ChargeRequest::new(PaymentMethod::BankTransfer { bank_transfer }, order_id, 44145);
+            let response = core.charge_typed(&request)?;
+            assert_eq!(response.status_code, "201");
+            assert_eq!(response.transaction_status, Some(TransactionStatus::Pending));
+            assert_eq!(response.va_numbers[0].bank, "bca");
+            Ok(())
+        }
+
         #[test]
         fn charge_fail_401() -> Result<(), MidtransError> {
             let mut core = generate_core_api_instance();
@@ -682,6 +1024,7 @@ mod test {
         }
 
         #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
         fn charge_fail_empty_param() -> Result<(), MidtransError> {
             let core = generate_core_api_instance();
             let parameters = String::from("");
@@ -897,12 +1240,97 @@ mod test {
         }
     }
 
+    mod payment_link {
+        use super::*;
+        use super::helper::*;
+        use crate::models::payment_link::{PaymentLinkExpiry, PaymentLinkExpiryUnit};
+
+        #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
+        fn create_get_and_delete() -> Result<(), MidtransError> {
+            let core = generate_core_api_instance();
+            let order_id = generate_order_id(30);
+            let mut request = PaymentLinkRequest::new(order_id.clone(), 190000);
+            request.usage_limit = Some(1);
+            request.customer_required = Some(false);
+            request.expiry = Some(PaymentLinkExpiry {
+                start_time: None,
+                duration: 1,
+                unit: PaymentLinkExpiryUnit::Days
+            });
+            request.enabled_payments = Some(vec!["bca_va".to_string(), "gopay".to_string()]);
+
+            let response = core.create_payment_link_typed(&request)?;
+            assert_eq!(response.order_id.as_deref(), Some(order_id.as_str()));
+            assert!(response.payment_url.is_some());
+
+            let response = core.get_payment_link(&order_id)?;
+            assert_eq!(response["usage_limit"], 1);
+            core.delete_payment_link(&order_id)?;
+            Ok(())
+        }
+
+        #[test]
+        fn get_not_found() {
+            let core = generate_core_api_instance();
+            match core.get_payment_link("payment-link-not-exist") {
+                Err(MidtransError::ApiError(e)) => assert_eq!(e.status_code, 404),
+                _ => panic!("expected ApiError")
+            }
+        }
+    }
+
+    mod invoice {
+        use super::*;
+        use super::helper::*;
+        use crate::models::invoice::{InvoiceCustomerDetails, InvoiceItem, InvoicePaymentType, InvoiceVirtualAccount};
+
+        #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
+        fn create_get_and_void() -> Result<(), MidtransError> {
+            let core = generate_core_api_instance();
+            let order_id = generate_order_id(31);
+            let today = chrono::offset::Local::now();
+            let customer_details = InvoiceCustomerDetails {
+                name: "John Doe".to_string(),
+                email: Some("john@example.com".to_string()),
+                ..Default::default()
+            };
+            let payment_type = InvoicePaymentType::VirtualAccount {
+                virtual_accounts: vec![InvoiceVirtualAccount { name: "bca_va".to_string(), number: None }]
+            };
+            let mut request = InvoiceRequest::new(
+                order_id.clone(),
+                format!("INV-{}", order_id),
+                today.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+                (today + chrono::Duration::days(7)).format("%Y-%m-%d %H:%M:%S %z").to_string(),
+                customer_details,
+                payment_type
+            );
+            request.item_details.push(InvoiceItem {
+                description: "Midtrans Bear".to_string(),
+                quantity: 1,
+                price: 100000,
+                ..Default::default()
+            });
+
+            let response = core.create_invoice_typed(&request)?;
+            assert_eq!(response.order_id.as_deref(), Some(order_id.as_str()));
+            let invoice_id = response.id.expect("invoice id");
+
+            let response = core.get_invoice_typed(&invoice_id)?;
+            assert_eq!(response.invoice_number, Some(format!("INV-{}", order_id)));
+            core.void_invoice(&invoice_id)?;
+            Ok(())
+        }
+    }
+
     mod tokenization {
         use super::*;
         use super::helper::*;
 
-        const PHONEUNREGISTERED: &'static str = "123450001";
-        const PHONEBLOCKED: &'static str = "123450002";
+        const PHONEUNREGISTERED: &str = "123450001";
+        const PHONEBLOCKED: &str = "123450002";
 
         #[test]
         fn link_account() -> Result<(), MidtransError> {
@@ -916,6 +1344,7 @@ mod test {
         }
 
         #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
         fn link_account_user_not_found() -> Result<(), MidtransError> {
             let tokenize = generate_core_api_instance();
             let parameters = generate_param_tokenization(PHONEUNREGISTERED);
@@ -926,6 +1355,7 @@ mod test {
         }
 
         #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
         fn link_account_user_blocked() -> Result<(), MidtransError> {
             let tokenize = generate_core_api_instance();
             let parameters = generate_param_tokenization(PHONEBLOCKED);
@@ -936,6 +1366,7 @@ mod test {
         }
 
         #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs the Midtrans sandbox")]
         fn link_account_phone_start_with_0() -> Result<(), MidtransError> {
             let tokenize = generate_core_api_instance();
             let parameters = generate_param_tokenization("081234567891");
@@ -1155,7 +1586,7 @@ mod test {
 
         #[test]
         fn status_server_key_change_via_property() -> Result<(), MidtransError> {
-            let mut core = CoreApi::new(false, "server_key".to_string()).build()?;
+            let mut core = new_core_api("server_key".to_string());
             core.api_config.server_key = server_key();
             let transaction_id = generate_order_id(1);
             let parameters = generate_param_charge_min(&transaction_id);
@@ -1168,8 +1599,8 @@ mod test {
 
         #[test]
         fn status_server_key_change_via_setter() -> Result<(), MidtransError> {
-            let mut core = CoreApi::new(false, server_key()).build()?;
-            assert_eq!(core.api_config.is_production, false);
+            let mut core = new_core_api(server_key());
+            assert!(!core.api_config.is_production);
             assert_eq!(core.api_config.server_key, server_key());
             let response = core.status("non-exist-order-id".to_string());
             assert!(response.is_err());
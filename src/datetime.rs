@@ -0,0 +1,75 @@
+//! Date Time
+//!
+//! Minimal clock in Western Indonesia Time (UTC+7), the timezone used by Midtrans.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current civil date and time in Western Indonesia Time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WibDateTime {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+    pub hour: i64,
+    pub minute: i64,
+    pub second: i64
+}
+
+impl WibDateTime {
+    pub fn now() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        Self::from_unix(seconds)
+    }
+
+    pub fn from_unix(seconds: i64) -> Self {
+        let seconds = seconds + 7 * 3600;
+        let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+        // civil from days, refer to: <http://howardhinnant.github.io/date_algorithms.html>
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self { year, month, day, hour: time / 3600, minute: time % 3600 / 60, second: time % 60 }
+    }
+
+    /// Format used by Core API, e.g. `2024-05-01 16:00:00`
+    #[cfg_attr(not(feature = "mock-server"), allow(dead_code))]
+    pub fn to_midtrans_string(self) -> String {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    /// ISO 8601 format used by SNAP BI, e.g. `2024-05-01T16:00:00+07:00`
+    #[cfg_attr(not(feature = "snap-bi"), allow(dead_code))]
+    pub fn to_iso8601(self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}+07:00",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_unix() {
+        // 2024-02-29 17:00:00 UTC
+        let datetime = WibDateTime::from_unix(1709226000);
+        assert_eq!(datetime.to_midtrans_string(), "2024-03-01 00:00:00");
+        assert_eq!(datetime.to_iso8601(), "2024-03-01T00:00:00+07:00");
+    }
+}
@@ -0,0 +1,321 @@
+//! Api Endpoints
+//!
+//! Operation, method and url of every Core API, Snap API and Iris API call, shared by the
+//! blocking clients and their [`crate::asynchronous`] counterparts.
+
+use reqwest::Method;
+use crate::{ApiConfig, MidtransError, config::Operation};
+
+/// Operation, method and url of a single Midtrans API call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub operation: Operation,
+    pub method: Method,
+    pub url: String
+}
+
+impl Endpoint {
+    pub fn new(operation: Operation, method: Method, url: String) -> Self {
+        Self { operation, method, url }
+    }
+
+    fn core(api_config: &ApiConfig, operation: Operation, method: Method, path: &str) -> Self {
+        Self::new(operation, method, format!("{}{}", api_config.get_core_api_base_url(), path))
+    }
+
+    fn iris(api_config: &ApiConfig, operation: Operation, method: Method, path: &str) -> Self {
+        Self::new(operation, method, format!("{}{}", api_config.get_iris_base_url(), path))
+    }
+
+    /// `/v2/charge`
+    pub(crate) fn charge(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::Charge, Method::POST, "/v2/charge")
+    }
+
+    /// `/v2/capture`
+    pub(crate) fn capture(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::Capture, Method::POST, "/v2/capture")
+    }
+
+    /// `/v2/card/register`
+    pub(crate) fn card_register(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::CardRegister, Method::GET, "/v2/card/register")
+    }
+
+    /// `/v2/token`
+    pub(crate) fn card_token(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::CardToken, Method::GET, "/v2/token")
+    }
+
+    /// `/v2/point_inquiry/<token_id>`
+    pub(crate) fn card_point_inquiry(api_config: &ApiConfig, token_id: &str) -> Self {
+        Self::core(api_config, Operation::CardPointInquiry, Method::GET, &format!("/v2/point_inquiry/{}", token_id))
+    }
+
+    /// `/v1/bins/<bin>`, `bin` must be 6 to 8 digits
+    pub(crate) fn card_bin(api_config: &ApiConfig, bin: &str) -> Result<Self, MidtransError> {
+        if !(6..=8).contains(&bin.len()) || !bin.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(MidtransError::InvalidParameters(
+                format!("card BIN must be 6 to 8 digits, got {:?}", bin)
+            ));
+        }
+        Ok(Self::core(api_config, Operation::CardBin, Method::GET, &format!("/v1/bins/{}", bin)))
+    }
+
+    /// `/v1/subscriptions`
+    pub(crate) fn create_subscription(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::Subscription, Method::POST, "/v1/subscriptions")
+    }
+
+    /// `/v1/subscriptions/<subscription_id>`
+    pub(crate) fn get_subscription(api_config: &ApiConfig, subscription_id: &str) -> Self {
+        Self::core(api_config, Operation::Subscription, Method::GET, &format!("/v1/subscriptions/{}", subscription_id))
+    }
+
+    /// `/v1/subscriptions/<subscription_id>/disable`
+    pub(crate) fn disable_subscription(api_config: &ApiConfig, subscription_id: &str) -> Self {
+        Self::core(api_config, Operation::Subscription, Method::POST, &format!("/v1/subscriptions/{}/disable", subscription_id))
+    }
+
+    /// `/v1/subscriptions/<subscription_id>/enable`
+    pub(crate) fn enable_subscription(api_config: &ApiConfig, subscription_id: &str) -> Self {
+        Self::core(api_config, Operation::Subscription, Method::POST, &format!("/v1/subscriptions/{}/enable", subscription_id))
+    }
+
+    /// `/v1/subscriptions/<subscription_id>`
+    pub(crate) fn update_subscription(api_config: &ApiConfig, subscription_id: &str) -> Self {
+        Self::core(api_config, Operation::Subscription, Method::PATCH, &format!("/v1/subscriptions/{}", subscription_id))
+    }
+
+    /// `/v2/pay/account`
+    pub(crate) fn link_payment_account(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::PaymentAccount, Method::POST, "/v2/pay/account")
+    }
+
+    /// `/v2/pay/account/<account_id>`
+    pub(crate) fn get_payment_account(api_config: &ApiConfig, account_id: &str) -> Self {
+        Self::core(api_config, Operation::PaymentAccount, Method::GET, &format!("/v2/pay/account/{}", account_id))
+    }
+
+    /// `/v2/pay/account/<account_id>/unbind`
+    pub(crate) fn unlink_payment_account(api_config: &ApiConfig, account_id: &str) -> Self {
+        Self::core(api_config, Operation::PaymentAccount, Method::POST, &format!("/v2/pay/account/{}/unbind", account_id))
+    }
+
+    /// `/v1/payment-links`
+    pub(crate) fn create_payment_link(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::PaymentLink, Method::POST, "/v1/payment-links")
+    }
+
+    /// `/v1/payment-links/<order_id>`
+    pub(crate) fn get_payment_link(api_config: &ApiConfig, order_id: &str) -> Self {
+        Self::core(api_config, Operation::PaymentLink, Method::GET, &format!("/v1/payment-links/{}", order_id))
+    }
+
+    /// `/v1/payment-links/<order_id>`
+    pub(crate) fn delete_payment_link(api_config: &ApiConfig, order_id: &str) -> Self {
+        Self::core(api_config, Operation::PaymentLink, Method::DELETE, &format!("/v1/payment-links/{}", order_id))
+    }
+
+    /// `/v1/invoices`
+    pub(crate) fn create_invoice(api_config: &ApiConfig) -> Self {
+        Self::core(api_config, Operation::Invoice, Method::POST, "/v1/invoices")
+    }
+
+    /// `/v1/invoices/<invoice_id>`
+    pub(crate) fn get_invoice(api_config: &ApiConfig, invoice_id: &str) -> Self {
+        Self::core(api_config, Operation::Invoice, Method::GET, &format!("/v1/invoices/{}", invoice_id))
+    }
+
+    /// `/v1/invoices/<invoice_id>/void`
+    pub(crate) fn void_invoice(api_config: &ApiConfig, invoice_id: &str) -> Self {
+        Self::core(api_config, Operation::Invoice, Method::PATCH, &format!("/v1/invoices/{}/void", invoice_id))
+    }
+
+    /// `/v2/<transaction_id>/status`
+    pub(crate) fn status(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::Status, Method::GET, &format!("/v2/{}/status", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/status/b2b`
+    pub(crate) fn status_b2b(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::StatusB2b, Method::GET, &format!("/v2/{}/status/b2b", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/approve`
+    pub(crate) fn approve(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::Approve, Method::POST, &format!("/v2/{}/approve", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/deny`
+    pub(crate) fn deny(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::Deny, Method::POST, &format!("/v2/{}/deny", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/cancel`
+    pub(crate) fn cancel(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::Cancel, Method::POST, &format!("/v2/{}/cancel", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/expire`
+    pub(crate) fn expire(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::Expire, Method::POST, &format!("/v2/{}/expire", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/refund`
+    pub(crate) fn refund(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::Refund, Method::POST, &format!("/v2/{}/refund", transaction_id))
+    }
+
+    /// `/v2/<transaction_id>/refund/online/direct`
+    pub(crate) fn refund_direct(api_config: &ApiConfig, transaction_id: &str) -> Self {
+        Self::core(api_config, Operation::RefundDirect, Method::POST, &format!("/v2/{}/refund/online/direct", transaction_id))
+    }
+
+    /// `/snap/v1/transactions` of Snap API
+    pub(crate) fn snap_transaction(api_config: &ApiConfig) -> Self {
+        Self::new(Operation::SnapTransaction, Method::POST, format!("{}/snap/v1/transactions", api_config.get_snap_base_url()))
+    }
+
+    /// `/api/v1/balance` of Iris API
+    pub(crate) fn iris_balance(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisBalance, Method::GET, "/api/v1/balance")
+    }
+
+    /// `/api/v1/beneficiaries` of Iris API
+    pub(crate) fn iris_create_beneficiaries(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisBeneficiary, Method::POST, "/api/v1/beneficiaries")
+    }
+
+    /// `/api/v1/beneficiaries/<alias_name>` of Iris API
+    pub(crate) fn iris_update_beneficiaries(api_config: &ApiConfig, alias_name: &str) -> Result<Self, MidtransError> {
+        let alias_name = path_segment("alias_name", alias_name)?;
+        Ok(Self::iris(api_config, Operation::IrisBeneficiary, Method::PATCH, &format!("/api/v1/beneficiaries/{}", alias_name)))
+    }
+
+    /// `/api/v1/beneficiaries` of Iris API
+    pub(crate) fn iris_get_beneficiaries(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisBeneficiary, Method::GET, "/api/v1/beneficiaries")
+    }
+
+    /// `/api/v1/payouts` of Iris API
+    pub(crate) fn iris_create_payouts(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisPayout, Method::POST, "/api/v1/payouts")
+    }
+
+    /// `/api/v1/payouts/approve` of Iris API
+    pub(crate) fn iris_approve_payouts(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisPayout, Method::POST, "/api/v1/payouts/approve")
+    }
+
+    /// `/api/v1/payouts/reject` of Iris API
+    pub(crate) fn iris_reject_payouts(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisPayout, Method::POST, "/api/v1/payouts/reject")
+    }
+
+    /// `/api/v1/payouts/<reference_no>` of Iris API
+    pub(crate) fn iris_payout_details(api_config: &ApiConfig, reference_no: &str) -> Result<Self, MidtransError> {
+        let reference_no = path_segment("reference_no", reference_no)?;
+        Ok(Self::iris(api_config, Operation::IrisPayout, Method::GET, &format!("/api/v1/payouts/{}", reference_no)))
+    }
+
+    /// `/api/v1/statements` of Iris API
+    pub(crate) fn iris_statements(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisStatement, Method::GET, "/api/v1/statements")
+    }
+
+    /// `/api/v1/channels` of Iris API
+    pub(crate) fn iris_topup_channels(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisTopupChannel, Method::GET, "/api/v1/channels")
+    }
+
+    /// `/api/v1/beneficiary_banks` of Iris API
+    pub(crate) fn iris_beneficiary_banks(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisBeneficiaryBank, Method::GET, "/api/v1/beneficiary_banks")
+    }
+
+    /// `/api/v1/account_validation` of Iris API
+    pub(crate) fn iris_account_validation(api_config: &ApiConfig) -> Self {
+        Self::iris(api_config, Operation::IrisAccountValidation, Method::GET, "/api/v1/account_validation")
+    }
+}
+
+/// Whether `value` can be put in a url path as is, without changing the endpoint it points to
+pub(crate) fn is_path_segment(value: &str) -> bool {
+    !value.is_empty()
+        && value != "."
+        && value != ".."
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_.~".contains(c))
+}
+
+fn path_segment<'a>(name: &str, value: &'a str) -> Result<&'a str, MidtransError> {
+    match is_path_segment(value) {
+        true => Ok(value),
+        false => Err(MidtransError::InvalidParameters(
+            format!("{} must only contain letters, digits, `-`, `_`, `.` and `~`, got {:?}", name, value)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+    use crate::{CoreApi, Transactions};
+    use crate::transport::FakeTransport;
+
+    #[test]
+    fn urls() -> Result<(), MidtransError> {
+        let api_config = ApiConfig::new(false, "server_key".to_string()).build();
+        assert_eq!(Endpoint::charge(&api_config).url, "https://api.sandbox.midtrans.com/v2/charge");
+        assert_eq!(
+            Endpoint::refund_direct(&api_config, "order-1"),
+            Endpoint::new(Operation::RefundDirect, Method::POST, "https://api.sandbox.midtrans.com/v2/order-1/refund/online/direct".to_string())
+        );
+        assert_eq!(Endpoint::card_bin(&api_config, "455633")?.url, "https://api.sandbox.midtrans.com/v1/bins/455633");
+        assert_eq!(Endpoint::snap_transaction(&api_config).url, "https://app.sandbox.midtrans.com/snap/v1/transactions");
+        Ok(())
+    }
+
+    #[test]
+    fn iris_path_parameters_must_be_segments() -> Result<(), MidtransError> {
+        let api_config = ApiConfig::new(false, "creator_key".to_string()).build();
+        assert_eq!(
+            Endpoint::iris_payout_details(&api_config, "ref-001")?.url,
+            "https://app.sandbox.midtrans.com/iris/api/v1/payouts/ref-001"
+        );
+        for value in ["", "..", "../balance", "a/b", "a?b", "a b"] {
+            assert!(matches!(Endpoint::iris_update_beneficiaries(&api_config, value), Err(MidtransError::InvalidParameters(_))), "{}", value);
+            assert!(matches!(Endpoint::iris_payout_details(&api_config, value), Err(MidtransError::InvalidParameters(_))), "{}", value);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn card_bin_must_be_digits() {
+        let api_config = ApiConfig::new(false, "server_key".to_string()).build();
+        for bin in ["45563", "455633123", "4556-33", ""] {
+            assert!(matches!(Endpoint::card_bin(&api_config, bin), Err(MidtransError::InvalidParameters(_))), "{}", bin);
+        }
+    }
+
+    #[test]
+    fn operation_timeout_with_prefixed_base_url() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .environment(crate::Environment::Custom {
+                core_url: "https://proxy.example.com/midtrans".to_string(),
+                snap_url: "https://proxy.example.com/midtrans-snap".to_string(),
+                iris_url: "https://proxy.example.com/midtrans-iris".to_string()
+            })
+            .timeout(Duration::from_secs(10))
+            .operation_timeout(Operation::Refund, Duration::from_secs(45))
+            .transport(transport.clone())
+            .build()?;
+        core.refund("order-1".to_string(), r#"{"amount": 5000}"#)?;
+        let request = transport.last_request();
+        assert_eq!(request.url, "https://proxy.example.com/midtrans/v2/order-1/refund");
+        assert_eq!(request.timeout, Some(Duration::from_secs(45)));
+        Ok(())
+    }
+}
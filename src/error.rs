@@ -33,7 +33,16 @@ pub enum MidtransError {
     RequestError(reqwest::Error),
     ParseError(ParseIntError),
     JsonDecodeError(serde_json::Error),
-    ApiError(ApiError)
+    ApiError(ApiError),
+    TransportError(Box<dyn error::Error + Send + Sync>),
+    TimeoutError(Box<dyn error::Error + Send + Sync>),
+    InvalidRequestOptions(String),
+    InvalidConfig(String),
+    InvalidParameters(String),
+    InvalidSignature,
+    InvalidNotification(String),
+    NotificationStoreError(Box<dyn error::Error + Send + Sync>),
+    SigningError(Box<dyn error::Error + Send + Sync>)
 }
 
 impl error::Error for MidtransError {
@@ -42,7 +51,16 @@ impl error::Error for MidtransError {
             MidtransError::RequestError(ref e) => Some(e),
             MidtransError::ParseError(ref e) => Some(e),
             MidtransError::JsonDecodeError(ref e) => Some(e),
-            MidtransError::ApiError(ref e) => Some(e)
+            MidtransError::ApiError(ref e) => Some(e),
+            MidtransError::TransportError(ref e) => Some(e.as_ref()),
+            MidtransError::TimeoutError(ref e) => Some(e.as_ref()),
+            MidtransError::InvalidRequestOptions(_) => None,
+            MidtransError::InvalidConfig(_) => None,
+            MidtransError::InvalidParameters(_) => None,
+            MidtransError::InvalidSignature => None,
+            MidtransError::InvalidNotification(_) => None,
+            MidtransError::NotificationStoreError(ref e) => Some(e.as_ref()),
+            MidtransError::SigningError(ref e) => Some(e.as_ref())
         }
     }
 }
@@ -53,7 +71,16 @@ impl fmt::Display for MidtransError {
             MidtransError::RequestError(_) => write!(f, "Request Error"),
             MidtransError::ParseError(_) => write!(f, "Parse Int Error"),
             MidtransError::JsonDecodeError(_) => write!(f, "Fail to decode JSON string"),
-            MidtransError::ApiError(_) => write!(f, "Midtrans API Error")
+            MidtransError::ApiError(_) => write!(f, "Midtrans API Error"),
+            MidtransError::TransportError(_) => write!(f, "Transport Error"),
+            MidtransError::TimeoutError(_) => write!(f, "Request Timeout"),
+            MidtransError::InvalidRequestOptions(msg) => write!(f, "Invalid Request Options: {}", msg),
+            MidtransError::InvalidConfig(msg) => write!(f, "Invalid Config: {}", msg),
+            MidtransError::InvalidParameters(msg) => write!(f, "Invalid Parameters: {}", msg),
+            MidtransError::InvalidSignature => write!(f, "Invalid Notification Signature"),
+            MidtransError::InvalidNotification(msg) => write!(f, "Invalid Notification: {}", msg),
+            MidtransError::NotificationStoreError(_) => write!(f, "Notification Store Error"),
+            MidtransError::SigningError(_) => write!(f, "Signing Error")
         }
     }
 }
@@ -72,7 +99,10 @@ impl fmt::Debug for MidtransError {
 
 impl From<reqwest::Error> for MidtransError {
     fn from(err: reqwest::Error) -> Self {
-        MidtransError::RequestError(err)
+        match err.is_timeout() {
+            true => MidtransError::TimeoutError(Box::new(err)),
+            false => MidtransError::RequestError(err)
+        }
     }
 }
 
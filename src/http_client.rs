@@ -1,86 +1,228 @@
 //! Http Client
 
 use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
 use reqwest::{
     self,
     header::{self, HeaderMap},
     Proxy,
     Method
 };
-use serde_json::Value;
-use crate::{MidtransError, error::ApiError};
-
-const CONTENT_TYPE: &'static str = "application/json";
-const ACCEPT: &'static str = "application/json";
-const USER_AGENT: &'static str = "midtransclient-rust";
-const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+use base64::{Engine, engine::general_purpose};
+use crate::parameters::Parameters;
+use serde_json::{Map, Value};
+use crate::{MidtransError, RetryPolicy, config::Operation, endpoint::Endpoint, error::ApiError};
+use crate::transport::{Transport, TransportRequest, ReqwestTransport};
+use crate::retry::Retry;
+
+const CONTENT_TYPE: &str = "application/json";
+const ACCEPT: &str = "application/json";
+const USER_AGENT: &str = "midtransclient-rust";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
 
 pub trait MidtransClient {
+    /// Custom [`Transport`] used to send requests.
+    /// `None` means the default [`ReqwestTransport`] is used.
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        None
+    }
+
+    /// [`RetryPolicy`] applied to transient failures.
+    /// `None` means every request is only attempted once.
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        None
+    }
+
+    /// Timeout of the whole request for `operation`.
+    /// `None` means no timeout.
+    fn get_timeout(&self, _operation: Operation) -> Option<Duration> {
+        None
+    }
+
     fn request(
         &self,
-        method: Method,
+        endpoint: Endpoint,
         server_key: &str,
-        api_url: &str,
-        parameters: &str,
+        parameters: &Value,
         custom_headers: Option<HeaderMap>,
         proxies: Option<Proxy>
     ) -> MidtransResult {
-        let parameters: HashMap<String, Value> = match parameters {
-            "" => HashMap::new(),
-            params => serde_json::from_str(params)?
-        };
+        let operation = endpoint.operation;
+        let request = build_request(endpoint, server_key, parameters, custom_headers)?;
+        send_with_retry(self, request, operation, proxies, parse_response)
+    }
 
-        let mut client = HttpClient::new();
-        if let Some(headers) = custom_headers {
-            client.custom_headers(headers);
-        }
-        if let Some(proxies) = proxies {
-            client.proxies(proxies);
+    /// Same as [`Self::request`] but also accepts responses that are not a JSON object,
+    /// e.g. the lists returned by Iris API
+    fn request_json(
+        &self,
+        endpoint: Endpoint,
+        server_key: &str,
+        parameters: &Value,
+        custom_headers: Option<HeaderMap>,
+        proxies: Option<Proxy>
+    ) -> Result<Value, MidtransError> {
+        let operation = endpoint.operation;
+        let request = build_request(endpoint, server_key, parameters, custom_headers)?;
+        send_with_retry(self, request, operation, proxies, parse_response_json)
+    }
+}
+
+/// Send `request` through the transport of `client` and decode the response with `parse`,
+/// retrying according to its [`RetryPolicy`]
+fn send_with_retry<C, T>(
+    client: &C,
+    mut request: TransportRequest,
+    operation: Operation,
+    proxies: Option<Proxy>,
+    parse: fn(u16, &HeaderMap, &str) -> Result<T, MidtransError>
+) -> Result<T, MidtransError>
+where
+    C: MidtransClient + ?Sized
+{
+    request.timeout = client.get_timeout(operation);
+
+    let mut retry = Retry::new(client.get_retry_policy());
+    loop {
+        let response = match client.get_transport() {
+            Some(transport) => transport.send(request.clone()),
+            None => ReqwestTransport::new(proxies.clone())?.send(request.clone())
+        }.and_then(|response| {
+            parse(response.status_code, &response.headers, &response.body)
+        });
+
+        let err = match response {
+            Err(err) => err,
+            response => return response
+        };
+        match retry.next_delay(&request, &err) {
+            Some(delay) => thread::sleep(delay),
+            None => return Err(err)
         }
+    }
+}
 
-        let request_builder = client.build()?
-            .request(method.clone(), api_url)
-            .basic_auth(server_key, Some(""));
+/// Prepare [`TransportRequest`] with auth, default and custom headers.
+/// `parameters` are sent as query string on `GET` and as JSON body otherwise,
+/// they must be a JSON object or `null` for no parameters.
+pub(crate) fn build_request(
+    endpoint: Endpoint,
+    server_key: &str,
+    parameters: &Value,
+    custom_headers: Option<HeaderMap>
+) -> Result<TransportRequest, MidtransError> {
+    let Endpoint { method, url: api_url, .. } = endpoint;
+    let empty = Map::new();
+    let parameters = match parameters {
+        Value::Object(parameters) => parameters,
+        Value::Null => &empty,
+        _ => return Err(MidtransError::InvalidParameters(
+            format!("expected a JSON object, got {}", parameters)
+        ))
+    };
+
+    let mut client = HttpClient::new();
+    if let Some(headers) = custom_headers {
+        client.custom_headers(headers);
+    }
 
-        let request_builder = match method {
-            Method::GET => request_builder.query(&parameters),
-            _ => request_builder.json(&parameters)
-        };
+    let mut headers = client.default_headers();
+    let credentials = general_purpose::STANDARD.encode(format!("{}:", server_key));
+    headers.insert(
+        header::AUTHORIZATION,
+        format!("Basic {}", credentials).parse()
+            .map_err(|e| MidtransError::TransportError(Box::new(e)))?
+    );
+
+    let (url, body) = match method {
+        Method::GET if parameters.is_empty() => (api_url, None),
+        Method::GET => {
+            let pairs = parameters.iter().map(|(key, value)| match value {
+                Value::String(value) => (key.clone(), value.clone()),
+                value => (key.clone(), value.to_string())
+            });
+            let url = reqwest::Url::parse_with_params(&api_url, pairs)
+                .map_err(|e| MidtransError::TransportError(Box::new(e)))?;
+            (url.to_string(), None)
+        },
+        _ => (api_url, Some(serde_json::to_string(parameters)?))
+    };
+
+    Ok(TransportRequest { method, url, headers, body, timeout: None })
+}
 
-        let response = Box::new(request_builder.send()?);
-        let response_header = response.headers().clone();
-        let response_status_code = response.status().as_u16();
-        let response_text = response.text()?;
-        let mut response_hashmap: HashMap<String, Value> = serde_json::from_str(&response_text)?;
+/// Serialize request parameters once into a JSON [`Value`], see [`Parameters`]
+pub(crate) fn to_parameters<P: Parameters + ?Sized>(parameters: &P) -> Result<Value, MidtransError> {
+    parameters.to_parameters()
+}
 
-        if !response_hashmap.contains_key("status_code") {
-            response_hashmap.insert("status_code".to_string(), Value::String(response_status_code.to_string()));
-        }
+/// Decode Midtrans API response body and turn `status_code` >= 400
+/// into `MidtransError::ApiError`.
+pub(crate) fn parse_response(
+    response_status_code: u16,
+    response_header: &HeaderMap,
+    response_text: &str
+) -> MidtransResult {
+    let response_hashmap: HashMap<String, Value> = serde_json::from_str(response_text)?;
+    check_response(response_status_code, response_header, response_hashmap)
+}
 
-        let status_code = match &response_hashmap["status_code"] {
-            Value::String(code) => code.parse::<u16>()?,
-            _ => 0
-        };
+/// Same as [`parse_response`] but also accepts JSON bodies that are not an object,
+/// those are only checked against the HTTP status code.
+pub(crate) fn parse_response_json(
+    response_status_code: u16,
+    response_header: &HeaderMap,
+    response_text: &str
+) -> Result<Value, MidtransError> {
+    match serde_json::from_str(response_text)? {
+        Value::Object(response) => {
+            let response = check_response(response_status_code, response_header, response.into_iter().collect())?;
+            Ok(Value::Object(response.into_iter().collect()))
+        },
+        response if response_status_code >= 400 => {
+            let response_hashmap = HashMap::from([
+                ("status_code".to_string(), Value::String(response_status_code.to_string())),
+                ("response".to_string(), response)
+            ]);
+            check_response(response_status_code, response_header, response_hashmap).map(Value::from_iter)
+        },
+        response => Ok(response)
+    }
+}
+
+fn check_response(
+    response_status_code: u16,
+    response_header: &HeaderMap,
+    mut response_hashmap: HashMap<String, Value>
+) -> MidtransResult {
+    if !response_hashmap.contains_key("status_code") {
+        response_hashmap.insert("status_code".to_string(), Value::String(response_status_code.to_string()));
+    }
 
-        if status_code >= 400 {
-            return Err(MidtransError::ApiError(
-                ApiError::new(
+    let status_code = match &response_hashmap["status_code"] {
+        Value::String(code) => code.parse::<u16>()?,
+        _ => 0
+    };
+
+    if status_code >= 400 {
+        return Err(MidtransError::ApiError(
+            ApiError::new(
+                status_code,
+                response_hashmap.clone(),
+                format!(
+                    "Midtrans API is returning API error. \nHTTP status code: {}. \nAPI Response: \nHeader {:#?} \nBody {:#?}",
                     status_code,
-                    response_hashmap.clone(),
-                    format!(
-                        "Midtrans API is returning API error. \nHTTP status code: {}. \nAPI Response: \nHeader {:#?} \nBody {:#?}",
-                        status_code,
-                        response_header,
-                        response_hashmap
-                    )
+                    response_header,
+                    response_hashmap
                 )
-            ));
-        }
-
-        Ok(response_hashmap)
+            )
+        ));
     }
+
+    Ok(response_hashmap)
 }
 
 /// Http Client Struct is wrapper to Rust's `reqwest` crate.
@@ -88,10 +230,12 @@ pub trait MidtransClient {
 pub struct HttpClient;
 
 impl HttpClient {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> HttpClientBuilder {
         HttpClientBuilder {
             custom_headers: None,
-            proxies: None
+            proxies: None,
+            connect_timeout: None
         }
     }
 }
@@ -99,7 +243,8 @@ impl HttpClient {
 /// Builder for HttpClient
 pub struct HttpClientBuilder {
     custom_headers: Option<header::HeaderMap>,
-    proxies: Option<reqwest::Proxy>
+    proxies: Option<reqwest::Proxy>,
+    connect_timeout: Option<Duration>
 }
 
 impl HttpClientBuilder {
@@ -113,7 +258,12 @@ impl HttpClientBuilder {
         self
     }
 
-    pub fn build(&self) -> reqwest::Result<reqwest::blocking::Client> {
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub(crate) fn default_headers(&self) -> header::HeaderMap {
         let user_agent = format!("{}/{}", USER_AGENT, VERSION);
         let mut headers = header::HeaderMap::new();
         headers.insert("content-type", header::HeaderValue::from_static(CONTENT_TYPE));
@@ -126,7 +276,29 @@ impl HttpClientBuilder {
             }
         }
 
-        let http_client = reqwest::blocking::Client::builder().default_headers(headers);
+        headers
+    }
+
+    pub fn build(&self) -> reqwest::Result<reqwest::blocking::Client> {
+        let mut http_client = reqwest::blocking::Client::builder().default_headers(self.default_headers());
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        let http_client = match &self.proxies {
+            Some(proxies) => http_client.proxy(proxies.clone()).build()?,
+            None => http_client.build()?
+        };
+
+        Ok(http_client)
+    }
+
+    /// Build non-blocking `reqwest::Client`, used by [`crate::asynchronous`]
+    #[cfg(feature = "async")]
+    pub fn build_async(&self) -> reqwest::Result<reqwest::Client> {
+        let mut http_client = reqwest::Client::builder().default_headers(self.default_headers());
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
         let http_client = match &self.proxies {
             Some(proxies) => http_client.proxy(proxies.clone()).build()?,
             None => http_client.build()?
@@ -177,25 +349,133 @@ mod test {
         }
     }
 
+    mod parameters {
+        use serde_json::json;
+        use super::*;
+
+        const URL: &str = "https://api.sandbox.midtrans.com/v2/charge";
+
+        #[test]
+        fn object_sent_as_body() -> Result<(), MidtransError> {
+            let parameters = to_parameters(&json!({"payment_type": "gopay"}))?;
+            let request = build_request(Endpoint::new(Operation::Other, Method::POST, URL.to_string()), "server_key", &parameters, None)?;
+            assert_eq!(request.body.as_deref(), Some(r#"{"payment_type":"gopay"}"#));
+
+            let request = build_request(Endpoint::new(Operation::Other, Method::GET, URL.to_string()), "server_key", &Value::Null, None)?;
+            assert_eq!(request.url, URL);
+            assert!(request.body.is_none());
+            Ok(())
+        }
+
+        #[test]
+        fn non_object_rejected() -> Result<(), MidtransError> {
+            for parameters in [json!([1, 2]), json!("gopay"), json!(10000)] {
+                let request = build_request(Endpoint::new(Operation::Other, Method::POST, URL.to_string()), "server_key", &parameters, None);
+                assert!(matches!(request, Err(MidtransError::InvalidParameters(_))));
+            }
+            Ok(())
+        }
+    }
+
+    mod parse_response {
+        use super::*;
+
+        #[test]
+        fn success_keeps_body() -> Result<(), MidtransError> {
+            let body = r#"{"status_code": "201", "transaction_status": "pending"}"#;
+            let response = parse_response(201, &HeaderMap::new(), body)?;
+            assert_eq!(response["status_code"], "201");
+            assert_eq!(response["transaction_status"], "pending");
+            Ok(())
+        }
+
+        #[test]
+        fn status_code_from_http_when_missing() -> Result<(), MidtransError> {
+            let response = parse_response(200, &HeaderMap::new(), r#"{"token": "abc"}"#)?;
+            assert_eq!(response["status_code"], "200");
+            Ok(())
+        }
+
+        #[test]
+        fn api_error_on_status_code_400_and_above() {
+            let body = r#"{"status_code": "404", "status_message": "Transaction doesn't exist."}"#;
+            let response = parse_response(200, &HeaderMap::new(), body);
+            match response {
+                Err(MidtransError::ApiError(e)) => {
+                    assert_eq!(e.status_code, 404);
+                    assert!(e.message.contains("Midtrans API is returning API error."));
+                },
+                _ => panic!("expected ApiError")
+            }
+        }
+
+        #[test]
+        fn not_json() {
+            let response = parse_response(200, &HeaderMap::new(), "<html></html>");
+            assert!(matches!(response, Err(MidtransError::JsonDecodeError(_))));
+        }
+
+        #[test]
+        fn json_accepts_list() -> Result<(), MidtransError> {
+            let response = parse_response_json(200, &HeaderMap::new(), r#"[{"code": "bca"}]"#)?;
+            assert_eq!(response[0]["code"], "bca");
+
+            let response = parse_response_json(500, &HeaderMap::new(), r#"["Internal Server Error"]"#);
+            match response {
+                Err(MidtransError::ApiError(e)) => assert_eq!(e.status_code, 500),
+                _ => panic!("expected ApiError")
+            }
+            Ok(())
+        }
+    }
+
     mod request {
         use serde_json::json;
         use super::*;
+        use std::sync::Arc;
+        #[cfg(not(feature = "mock-server"))]
         use std::env;
 
-        struct TestClient;
+        /// Sends through the sandbox, or the shared mock with the `mock-server` feature
+        struct TestClient {
+            transport: Option<Arc<dyn Transport>>
+        }
+
+        impl TestClient {
+            fn new() -> Self {
+                #[cfg(not(feature = "mock-server"))]
+                let transport = None;
+                #[cfg(feature = "mock-server")]
+                let transport = Some(crate::mock_server::shared() as Arc<dyn Transport>);
+                Self { transport }
+            }
+        }
 
-        impl MidtransClient for TestClient {}
+        impl MidtransClient for TestClient {
+            fn get_transport(&self) -> Option<&dyn Transport> {
+                self.transport.as_deref()
+            }
+        }
 
+        #[cfg(not(feature = "mock-server"))]
         fn server_key() -> String {
             env::var("MIDTRANS_SERVER_KEY").expect("SERVER_KEY NOT FOUND")
         }
 
+        #[cfg(feature = "mock-server")]
+        fn server_key() -> String {
+            crate::mock_server::TEST_SERVER_KEY.to_string()
+        }
+
         fn generate_order_id(test_number: u8) -> String {
             let now = chrono::offset::Local::now().format("%Y%m%d%H%M%S").to_string();
+            // tests share one mock, keep their order ids apart within the same second
+            #[cfg(feature = "mock-server")]
+            let now = format!("{}-{}", now, &uuid::Uuid::new_v4().simple().to_string()[..8]);
             format!("rust-midtransclient-test{}-{}", test_number, now)
         }
 
-        fn generate_param_min(order_id: &str) -> String {
+        fn generate_param_min(order_id: &str) -> Value {
             json!({
                 "transaction_details": {
                     "order_id": order_id,
@@ -203,18 +483,17 @@ mod test {
                 }, "credit_card":{
                     "secure" : true
                 }
-            }).to_string()
+            })
         }
 
         #[test]
         fn can_raw_request_to_snap() -> Result<(), MidtransError> {
-            let http_client = TestClient{};
+            let http_client = TestClient::new();
             let order_id = generate_order_id(1);
             let parameters = generate_param_min(&order_id);
             let response = http_client.request(
-                Method::POST,
+                Endpoint::new(Operation::SnapTransaction, Method::POST, "https://app.sandbox.midtrans.com/snap/v1/transactions".to_string()),
                 &server_key(),
-                "https://app.sandbox.midtrans.com/snap/v1/transactions",
                 &parameters,
                 None,
                 None
@@ -226,13 +505,12 @@ mod test {
 
         #[test]
         fn fail_request_401_to_snap() -> Result<(), MidtransError> {
-            let http_client = TestClient{};
+            let http_client = TestClient::new();
             let order_id = generate_order_id(1);
             let parameters = generate_param_min(&order_id);
             let response = http_client.request(
-                Method::POST,
+                Endpoint::new(Operation::SnapTransaction, Method::POST, "https://app.sandbox.midtrans.com/snap/v1/transactions".to_string()),
                 "wrong-server-key",
-                "https://app.sandbox.midtrans.com/snap/v1/transactions",
                 &parameters,
                 None,
                 None
@@ -245,13 +523,13 @@ mod test {
         }
 
         #[test]
+        #[cfg_attr(feature = "mock-server", ignore = "needs a non-JSON page from midtrans.com")]
         fn response_not_json_exception() -> Result<(), MidtransError> {
-            let http_client = TestClient{};
+            let http_client = TestClient::new();
             let response = http_client.request(
-                Method::GET,
-                "",
-                "https://midtrans.com/",
+                Endpoint::new(Operation::Other, Method::GET, "https://midtrans.com/".to_string()),
                 "",
+                &Value::Null,
                 None,
                 None
             );
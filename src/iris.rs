@@ -0,0 +1,475 @@
+//! Iris
+//!
+//! Client of Midtrans Iris disbursement API, used to pay out to beneficiaries.
+//! Requests are authenticated with the creator key, except approving and rejecting
+//! payouts that need the approver key.
+//!
+//! Note: Iris API has no endpoint to delete a beneficiary.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{header::HeaderMap, Proxy};
+use crate::parameters::Parameters;
+use serde_json::Value;
+use crate::{ApiConfig, MidtransError, RetryPolicy, Transport};
+use crate::http_client::{MidtransClient, to_parameters};
+use crate::transport::ReqwestTransport;
+use crate::config::{ApiConfigBuilder, Environment, Operation};
+use crate::endpoint::Endpoint;
+
+type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
+type MidtransListResult = Result<Vec<HashMap<String, Value>>, MidtransError>;
+
+/// Iris struct used to do request to Midtrans Iris API
+pub struct Iris {
+    pub api_config: ApiConfig,
+    approver_key: Option<String>,
+    transport: Arc<dyn Transport>
+}
+
+impl MidtransClient for Iris {
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        Some(self.transport.as_ref())
+    }
+
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.api_config.get_retry_policy().as_ref()
+    }
+
+    fn get_timeout(&self, operation: Operation) -> Option<Duration> {
+        self.api_config.get_timeout(operation)
+    }
+}
+
+pub struct IrisBuilder {
+    api_config: ApiConfigBuilder,
+    approver_key: Option<String>,
+    transport: Option<Arc<dyn Transport>>
+}
+
+impl IrisBuilder {
+    /// Override the environment chosen by `is_production`, e.g. `Environment::Custom`
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.api_config.environment(environment);
+        self
+    }
+
+    /// Key used to approve and reject payouts
+    pub fn approver_key(&mut self, approver_key: String) -> &mut Self {
+        self.approver_key = Some(approver_key);
+        self
+    }
+
+    pub fn custom_headers(&mut self, custom_headers: HeaderMap) -> &mut Self {
+        self.api_config.custom_header(custom_headers);
+        self
+    }
+
+    pub fn proxies(&mut self, proxies: Proxy) -> &mut Self {
+        self.api_config.proxies(proxies);
+        self
+    }
+
+    /// Retry transient failures of safe requests, see [`RetryPolicy`]
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.api_config.retry_policy(retry_policy);
+        self
+    }
+
+    /// Timeout for establishing the connection to Midtrans
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.connect_timeout(timeout);
+        self
+    }
+
+    /// Timeout of the whole request, exceeding it returns `MidtransError::TimeoutError`
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.timeout(timeout);
+        self
+    }
+
+    /// Override `timeout` for specific [`Operation`], e.g. `Operation::IrisPayout`
+    pub fn operation_timeout(&mut self, operation: Operation, timeout: Duration) -> &mut Self {
+        self.api_config.operation_timeout(operation, timeout);
+        self
+    }
+
+    /// Use custom [`Transport`] instead of the default `reqwest` one
+    pub fn transport(&mut self, transport: Arc<dyn Transport>) -> &mut Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Use preconfigured `reqwest::blocking::Client` for every request of this instance.
+    pub fn http_client(&mut self, http_client: reqwest::blocking::Client) -> &mut Self {
+        self.transport = Some(Arc::new(ReqwestTransport::from(http_client)));
+        self
+    }
+
+    pub fn build(&self) -> Result<Iris, MidtransError> {
+        let api_config = self.api_config.build();
+
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::from_config(&api_config)?)
+        };
+
+        Ok(Iris { api_config, approver_key: self.approver_key.clone(), transport })
+    }
+}
+
+impl Iris {
+    /// `creator_key` is the Iris API key of the creator role,
+    /// set the approver one with [`IrisBuilder::approver_key`]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(is_production: bool, creator_key: String) -> IrisBuilder {
+        IrisBuilder {
+            api_config: ApiConfig::new(is_production, creator_key),
+            approver_key: None,
+            transport: None
+        }
+    }
+
+    /// Setter for custom [`Transport`]
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        self.transport = transport;
+    }
+
+    /// Getter for the approver key
+    pub fn get_approver_key(&self) -> Option<&str> {
+        self.approver_key.as_deref()
+    }
+
+    /// Setter for the approver key
+    pub fn set_approver_key(&mut self, approver_key: String) {
+        self.approver_key = Some(approver_key);
+    }
+
+    fn approver_key(&self) -> Result<&str, MidtransError> {
+        self.get_approver_key().ok_or_else(|| MidtransError::InvalidConfig(
+            "approver key is required to approve or reject payouts".to_string()
+        ))
+    }
+
+    /// Trigger `/api/v1/balance` API call to Iris API
+    ///
+    /// Get the balance of the merchant account
+    ///
+    pub fn get_balance(&self) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_balance(&self.api_config),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/beneficiaries` API call to Iris API
+    ///
+    /// Create a new beneficiary
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/create-beneficiaries>)
+    ///
+    pub fn create_beneficiaries<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_create_beneficiaries(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/beneficiaries/<alias_name>` API call to Iris API
+    ///
+    /// Update an existing beneficiary
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/update-beneficiaries>)
+    ///
+    pub fn update_beneficiaries<P: Parameters + ?Sized>(&self, alias_name: &str, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_update_beneficiaries(&self.api_config, alias_name)?,
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/beneficiaries` API call to Iris API
+    ///
+    /// List every beneficiary
+    ///
+    pub fn get_beneficiaries(&self) -> MidtransListResult {
+        let response = self.request_json(
+            Endpoint::iris_get_beneficiaries(&self.api_config),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Trigger `/api/v1/payouts` API call to Iris API
+    ///
+    /// Create one or more payouts, to be approved with the approver key
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/create-payouts>)
+    ///
+    pub fn create_payouts<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_create_payouts(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/payouts/approve` API call to Iris API with the approver key
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/approve-payouts>)
+    ///
+    pub fn approve_payouts<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_approve_payouts(&self.api_config),
+            self.approver_key()?,
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/payouts/reject` API call to Iris API with the approver key
+    ///
+    /// (more params detail refer to: <https://docs.midtrans.com/reference/reject-payouts>)
+    ///
+    pub fn reject_payouts<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_reject_payouts(&self.api_config),
+            self.approver_key()?,
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/payouts/<reference_no>` API call to Iris API
+    ///
+    /// Get details of a payout
+    ///
+    pub fn get_payout_details(&self, reference_no: &str) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_payout_details(&self.api_config, reference_no)?,
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/statements` API call to Iris API
+    ///
+    /// List the statement history, `parameters` may contain `from_date` and `to_date`
+    /// formatted as `YYYY-MM-DD`
+    ///
+    pub fn get_transaction_history<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransListResult {
+        let response = self.request_json(
+            Endpoint::iris_statements(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Trigger `/api/v1/channels` API call to Iris API
+    ///
+    /// List the channels to top up the merchant balance
+    ///
+    pub fn get_topup_channels(&self) -> MidtransListResult {
+        let response = self.request_json(
+            Endpoint::iris_topup_channels(&self.api_config),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Trigger `/api/v1/beneficiary_banks` API call to Iris API
+    ///
+    /// List the banks supported as beneficiary bank, in `beneficiary_banks`
+    ///
+    pub fn get_beneficiary_banks(&self) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_beneficiary_banks(&self.api_config),
+            self.api_config.get_server_key(),
+            &Value::Null,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+
+    /// Trigger `/api/v1/account_validation` API call to Iris API
+    ///
+    /// Validate a bank account, `parameters` contains `bank` and `account`
+    ///
+    pub fn validate_bank_account<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        let response = self.request(
+            Endpoint::iris_account_validation(&self.api_config),
+            self.api_config.get_server_key(),
+            &to_parameters(parameters)?,
+            self.api_config.get_custom_headers().clone(),
+            self.api_config.get_proxies().clone()
+        )?;
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose};
+    use reqwest::{Method, header};
+    use serde_json::json;
+    use crate::transport::FakeTransport;
+
+    const CREATOR_KEY: &str = "IRIS-creator";
+    const APPROVER_KEY: &str = "IRIS-approver";
+
+    fn iris(transport: Arc<FakeTransport>) -> Iris {
+        Iris::new(false, CREATOR_KEY.to_string())
+            .approver_key(APPROVER_KEY.to_string())
+            .transport(transport)
+            .build()
+            .unwrap()
+    }
+
+    fn authorization(key: &str) -> String {
+        format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:", key)))
+    }
+
+    #[test]
+    fn create_payouts_with_creator_key() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(201, json!({
+            "payouts": [{"status": "queued", "reference_no": "ref-1"}]
+        }));
+        let response = iris(transport.clone()).create_payouts(&json!({
+            "payouts": [{
+                "beneficiary_name": "Jon Snow",
+                "beneficiary_account": "1172993826",
+                "beneficiary_bank": "bni",
+                "amount": "100000.00",
+                "notes": "Payout April 17"
+            }]
+        }))?;
+        assert_eq!(response["payouts"][0]["reference_no"], "ref-1");
+
+        let request = transport.last_request();
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.url, "https://app.sandbox.midtrans.com/iris/api/v1/payouts");
+        assert_eq!(request.headers[header::AUTHORIZATION], authorization(CREATOR_KEY).as_str());
+        Ok(())
+    }
+
+    #[test]
+    fn approve_payouts_with_approver_key() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(202, json!({"status": "ok"}));
+        iris(transport.clone()).approve_payouts(&json!({"reference_nos": ["ref-1"], "otp": "335163"}))?;
+
+        let request = transport.last_request();
+        assert_eq!(request.url, "https://app.sandbox.midtrans.com/iris/api/v1/payouts/approve");
+        assert_eq!(request.headers[header::AUTHORIZATION], authorization(APPROVER_KEY).as_str());
+
+        let iris = Iris::new(false, CREATOR_KEY.to_string()).transport(transport).build()?;
+        let response = iris.reject_payouts(&json!({"reference_nos": ["ref-1"], "reject_reason": "wrong amount"}));
+        assert!(matches!(response, Err(MidtransError::InvalidConfig(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn operation_timeout() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, json!({"reference_no": "ref-1", "status": "queued"}));
+        let iris = Iris::new(false, CREATOR_KEY.to_string())
+            .timeout(Duration::from_secs(10))
+            .operation_timeout(Operation::IrisPayout, Duration::from_secs(30))
+            .transport(transport.clone())
+            .build()?;
+
+        iris.get_payout_details("ref-1")?;
+        assert_eq!(transport.last_request().timeout, Some(Duration::from_secs(30)));
+        iris.get_balance()?;
+        assert_eq!(transport.last_request().timeout, Some(Duration::from_secs(10)));
+        Ok(())
+    }
+
+    #[test]
+    fn path_parameters_validated() {
+        let transport = FakeTransport::new(200, json!({"status": "ok"}));
+        let iris = iris(transport.clone());
+        let response = iris.update_beneficiaries("../payouts", &json!({"name": "Jon Snow"}));
+        assert!(matches!(response, Err(MidtransError::InvalidParameters(_))));
+        let response = iris.get_payout_details("ref-1/approve");
+        assert!(matches!(response, Err(MidtransError::InvalidParameters(_))));
+        assert!(transport.requests().is_empty());
+    }
+
+    #[test]
+    fn list_responses() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, json!([
+            {"name": "Jon Snow", "bank": "bni", "account": "1172993826", "alias_name": "jonsnow"}
+        ]));
+        let beneficiaries = iris(transport.clone()).get_beneficiaries()?;
+        assert_eq!(beneficiaries[0]["alias_name"], "jonsnow");
+
+        let statements = iris(transport.clone())
+            .get_transaction_history(&json!({"from_date": "2024-01-01", "to_date": "2024-01-31"}))?;
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            transport.last_request().url,
+            "https://app.sandbox.midtrans.com/iris/api/v1/statements?from_date=2024-01-01&to_date=2024-01-31"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn api_error() {
+        let transport = FakeTransport::new(400, json!({
+            "error_message": "An error occurred when creating beneficiary",
+            "errors": {"account": ["Account has already been taken"]}
+        }));
+        match iris(transport).create_beneficiaries(&json!({"alias_name": "jonsnow"})) {
+            Err(MidtransError::ApiError(e)) => {
+                assert_eq!(e.status_code, 400);
+                assert!(e.response.contains_key("errors"));
+            },
+            _ => panic!("expected ApiError")
+        }
+    }
+}
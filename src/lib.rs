@@ -118,20 +118,48 @@
 
 pub mod config;
 pub mod http_client;
+pub mod endpoint;
 pub mod core_api;
 pub mod snap;
 pub mod error;
 pub mod transactions;
+pub mod transport;
+pub mod retry;
+pub mod request_options;
+pub mod parameters;
+pub mod models;
+pub mod signature;
+pub mod webhook;
+pub mod cassette;
+pub mod iris;
+#[cfg(feature = "mock-server")]
+pub mod mock_server;
+#[cfg(feature = "snap-bi")]
+pub mod snap_bi;
+#[cfg(any(feature = "mock-server", feature = "snap-bi"))]
+mod datetime;
+#[cfg(feature = "async")]
+pub mod asynchronous;
 
 #[doc(inline)]
-pub use config::ApiConfig;
+pub use config::{ApiConfig, Environment};
 #[doc(inline)]
 pub use core_api::CoreApi;
 #[doc(inline)]
 pub use snap::Snap;
 #[doc(inline)]
+pub use iris::Iris;
+#[doc(inline)]
 pub use http_client::MidtransClient;
 #[doc(inline)]
 pub use error::MidtransError;
 #[doc(inline)]
 pub use transactions::Transactions;
+#[doc(inline)]
+pub use transport::Transport;
+#[doc(inline)]
+pub use retry::RetryPolicy;
+#[doc(inline)]
+pub use request_options::RequestOptions;
+#[doc(inline)]
+pub use parameters::{Json, Parameters};
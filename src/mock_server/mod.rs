@@ -0,0 +1,473 @@
+//! Mock Server
+//!
+//! In-memory implementation of the Midtrans Core API, Snap and transaction
+//! management endpoints for offline testing, available with the `mock-server`
+//! cargo feature.
+//!
+//! [`MockMidtrans`] keeps charges, subscriptions and pay accounts in memory and
+//! moves transactions through the [documented lifecycle](crate::models::lifecycle).
+//! It can be plugged into `CoreApi` / `Snap` directly as a [`Transport`], or
+//! served over HTTP with [`MockServer`] for clients in other processes.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use midtransclient::{CoreApi, MidtransError};
+//! use midtransclient::mock_server::MockMidtrans;
+//! use midtransclient::models::TransactionStatus;
+//! use serde_json::json;
+//!
+//! # fn main() -> Result<(), MidtransError> {
+//! let mock = Arc::new(MockMidtrans::new("SB-Mid-server-TEST".to_string()).build());
+//! let core = CoreApi::new(false, "SB-Mid-server-TEST".to_string())
+//!     .transport(mock.clone())
+//!     .build()?;
+//!
+//! let parameters = json!({
+//!     "payment_type": "bank_transfer",
+//!     "transaction_details": {"order_id": "order-1", "gross_amount": 10000},
+//!     "bank_transfer": {"bank": "bca"}
+//! }).to_string();
+//! core.charge(&parameters)?;
+//!
+//! // the customer pays
+//! mock.set_status("order-1", TransactionStatus::Settlement)?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod routes;
+mod state;
+mod server;
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use serde_json::{json, Map, Value};
+use crate::MidtransError;
+use crate::http_client::parse_response;
+use crate::models::{TransactionStatus, FraudStatus};
+use crate::transport::{Transport, TransportRequest, TransportResponse};
+use state::{MockState, now};
+
+#[doc(inline)]
+pub use server::MockServer;
+
+/// In-memory Midtrans
+pub struct MockMidtrans {
+    server_key: String,
+    notification_url: Option<String>,
+    state: Mutex<MockState>
+}
+
+pub struct MockMidtransBuilder {
+    server_key: String,
+    notification_url: Option<String>
+}
+
+impl MockMidtransBuilder {
+    /// Send HTTP notifications to `notification_url` whenever a transaction is
+    /// created or changes status
+    pub fn notification_url(&mut self, notification_url: String) -> &mut Self {
+        self.notification_url = Some(notification_url);
+        self
+    }
+
+    pub fn build(&self) -> MockMidtrans {
+        MockMidtrans {
+            server_key: self.server_key.clone(),
+            notification_url: self.notification_url.clone(),
+            state: Mutex::new(MockState::new(self.server_key.clone()))
+        }
+    }
+}
+
+impl MockMidtrans {
+    /// Only requests authenticated with `server_key` are accepted
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(server_key: String) -> MockMidtransBuilder {
+        MockMidtransBuilder { server_key, notification_url: None }
+    }
+
+    /// Handle a request as Midtrans would, only the path of the url is used for routing
+    pub fn handle(&self, request: &TransportRequest) -> TransportResponse {
+        let (status_code, body) = self.route(request);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        TransportResponse { status_code, headers, body: body.to_string() }
+    }
+
+    fn route(&self, request: &TransportRequest) -> (u16, Value) {
+        let url = match reqwest::Url::parse(&request.url) {
+            Ok(url) => url,
+            Err(_) => return (400, json!({"status_code": "400", "status_message": "Invalid url"}))
+        };
+        let segments: Vec<&str> = url.path_segments()
+            .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+            .unwrap_or_default();
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+        // card tokenization is authenticated with the client key in the query string
+        let is_tokenization = segments == ["v2", "token"] && query.contains_key("client_key");
+        if !is_tokenization && !self.is_authorized(&request.headers) {
+            return routes::unauthorized()
+        }
+
+        let body = match request.body.as_deref().map(str::trim) {
+            None | Some("") => Map::new(),
+            Some(body) => match serde_json::from_str::<Value>(body) {
+                Ok(Value::Object(body)) => body,
+                _ => return (400, json!({
+                    "status_code": "400",
+                    "status_message": "Request body is not a valid JSON object"
+                }))
+            }
+        };
+
+        let (response, notifications) = {
+            let mut state = self.state();
+            let origin = url.origin().ascii_serialization();
+            let response = routes::route(&mut state, &request.method, &origin, &segments, &query, &body);
+            (response, state.take_notifications())
+        };
+
+        if let Some(url) = &self.notification_url {
+            for notification in notifications {
+                let url = url.clone();
+                thread::spawn(move || post_notification(&url, &notification));
+            }
+        }
+        response
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let expected = format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:", self.server_key)));
+        headers.get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == expected)
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Move a transaction (by `transaction_id` or `order_id`) to `status`, as if
+    /// the customer paid or the payment expired, and send its HTTP notification.
+    ///
+    /// Returns the status code the notification url answered with, if configured.
+    /// Illegal transitions fail with the `412` API error Midtrans returns.
+    pub fn set_status(&self, id: &str, status: TransactionStatus) -> Result<Option<u16>, MidtransError> {
+        {
+            let mut state = self.state();
+            let transaction = state.find_mut(id).ok_or_else(|| api_error(404, "Transaction doesn't exist."))?;
+            if !transaction.status.can_transition_to(status) {
+                return Err(api_error(412, "Merchant cannot modify the status of the transaction"))
+            }
+            transaction.status = status;
+            if status == TransactionStatus::Settlement {
+                transaction.settlement_time = Some(now());
+            }
+        }
+        self.send_notification(id)
+    }
+
+    /// Shorthand for `set_status(id, TransactionStatus::Settlement)`
+    pub fn settle(&self, id: &str) -> Result<Option<u16>, MidtransError> {
+        self.set_status(id, TransactionStatus::Settlement)
+    }
+
+    /// Flag a transaction by fraud detection, e.g. `FraudStatus::Challenge`
+    /// to test `approve` and `deny`
+    pub fn set_fraud_status(&self, id: &str, fraud_status: FraudStatus) -> Result<(), MidtransError> {
+        let mut state = self.state();
+        let transaction = state.find_mut(id).ok_or_else(|| api_error(404, "Transaction doesn't exist."))?;
+        transaction.fraud_status = Some(fraud_status);
+        Ok(())
+    }
+
+    /// Change the `account_status` of a linked pay account, e.g. to `ENABLED`
+    /// after the customer approved the link
+    pub fn set_account_status(&self, account_id: &str, account_status: &str) -> Result<(), MidtransError> {
+        let mut state = self.state();
+        let account = state.pay_accounts.get_mut(account_id)
+            .ok_or_else(|| api_error(404, "Account doesn't exist."))?;
+        account.insert("account_status".to_string(), json!(account_status));
+        Ok(())
+    }
+
+    /// Current HTTP notification body of a transaction, with a valid `signature_key`
+    pub fn notification(&self, id: &str) -> Option<HashMap<String, Value>> {
+        self.state().notification(id).map(|body| body.into_iter().collect())
+    }
+
+    /// Send the current HTTP notification of a transaction again, as Midtrans
+    /// does until it gets a `200`. Returns `None` without a notification url.
+    pub fn send_notification(&self, id: &str) -> Result<Option<u16>, MidtransError> {
+        let notification = self.state().notification(id)
+            .ok_or_else(|| api_error(404, "Transaction doesn't exist."))?;
+        match &self.notification_url {
+            Some(url) => {
+                let url = url.clone();
+                let status_code = thread::spawn(move || post_notification(&url, &notification))
+                    .join()
+                    .map_err(|_| MidtransError::TransportError("notification thread panicked".into()))??;
+                Ok(Some(status_code))
+            },
+            None => Ok(None)
+        }
+    }
+}
+
+/// Server key accepted by [`shared`]
+#[cfg(test)]
+pub(crate) const TEST_SERVER_KEY: &str = "SB-Mid-server-TEST";
+
+/// Client key used by the API client test suites against [`shared`]
+#[cfg(test)]
+pub(crate) const TEST_CLIENT_KEY: &str = "SB-Mid-client-TEST";
+
+/// Mock shared by the API client test suites, so every instance created
+/// during a test sees the transactions of the others, as with the sandbox
+#[cfg(test)]
+pub(crate) fn shared() -> std::sync::Arc<MockMidtrans> {
+    static MOCK: std::sync::OnceLock<std::sync::Arc<MockMidtrans>> = std::sync::OnceLock::new();
+    MOCK.get_or_init(|| std::sync::Arc::new(MockMidtrans::new(TEST_SERVER_KEY.to_string()).build())).clone()
+}
+
+impl Transport for MockMidtrans {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        Ok(self.handle(&request))
+    }
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl crate::asynchronous::Transport for MockMidtrans {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        Ok(self.handle(&request))
+    }
+}
+
+/// Blocking POST of a notification, must not run on an async runtime thread
+fn post_notification(url: &str, notification: &Map<String, Value>) -> Result<u16, MidtransError> {
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .json(notification)
+        .send()?;
+    Ok(response.status().as_u16())
+}
+
+fn api_error(status_code: u16, message: &str) -> MidtransError {
+    let body = json!({"status_code": status_code.to_string(), "status_message": message}).to_string();
+    match parse_response(status_code, &HeaderMap::new(), &body) {
+        Err(err) => err,
+        Ok(_) => unreachable!("status code {} is an API error", status_code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, mpsc};
+    use crate::{CoreApi, Snap, Transactions};
+    use crate::models::TransactionStatus;
+    use crate::signature;
+
+    const SERVER_KEY: &str = "SB-Mid-server-MOCK";
+
+    fn core_api(mock: &Arc<MockMidtrans>) -> CoreApi {
+        CoreApi::new(false, SERVER_KEY.to_string())
+            .transport(mock.clone())
+            .build()
+            .unwrap()
+    }
+
+    fn bank_transfer(order_id: &str) -> String {
+        json!({
+            "payment_type": "bank_transfer",
+            "transaction_details": {"order_id": order_id, "gross_amount": 10000},
+            "bank_transfer": {"bank": "bca"}
+        }).to_string()
+    }
+
+    fn status_code(result: Result<HashMap<String, Value>, MidtransError>) -> u16 {
+        match result {
+            Err(MidtransError::ApiError(e)) => e.status_code,
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn charge_settle_refund() {
+        let mock = Arc::new(MockMidtrans::new(SERVER_KEY.to_string()).build());
+        let core = core_api(&mock);
+
+        let charge = core.charge(&bank_transfer("order-1")).unwrap();
+        assert_eq!(charge["status_code"], "201");
+        assert_eq!(charge["va_numbers"][0]["bank"], "bca");
+
+        mock.settle("order-1").unwrap();
+        let status = core.status_typed("order-1".to_string()).unwrap();
+        assert_eq!(status.transaction_status, Some(TransactionStatus::Settlement));
+
+        let refund = core.refund("order-1".to_string(), r#"{"amount": 4000}"#).unwrap();
+        assert_eq!(refund["transaction_status"], "partial_refund");
+        let refund = core.refund_typed("order-1".to_string(), r#"{"refund_key": "r-2"}"#).unwrap();
+        assert_eq!(refund.transaction_status, Some(TransactionStatus::Refund));
+        assert_eq!(refund.refund_amount.as_deref(), Some("6000.00"));
+
+        let notification = mock.notification("order-1").unwrap();
+        assert!(signature::verify_notification(&notification, SERVER_KEY).is_ok());
+    }
+
+    #[test]
+    fn api_errors() {
+        let mock = Arc::new(MockMidtrans::new(SERVER_KEY.to_string()).build());
+        let core = core_api(&mock);
+        core.charge(&bank_transfer("order-1")).unwrap();
+
+        assert_eq!(status_code(core.charge(&bank_transfer("order-1"))), 406);
+        assert_eq!(status_code(core.status("order-2".to_string())), 404);
+        assert_eq!(status_code(core.approve("order-1".to_string())), 412);
+        assert_eq!(status_code(core.refund("order-1".to_string(), "{}")), 412);
+        assert_eq!(status_code(core.expire("order-1".to_string())), 407);
+        assert_eq!(status_code(core.cancel("order-1".to_string())), 412);
+        assert!(mock.settle("order-1").is_err());
+
+        let mut core = core;
+        core.api_config.set_server_key("invalid_key".to_string());
+        assert_eq!(status_code(core.status("order-1".to_string())), 401);
+    }
+
+    #[test]
+    fn credit_card_authorize_capture() {
+        let mock = Arc::new(MockMidtrans::new(SERVER_KEY.to_string()).build());
+        let core = core_api(&mock);
+        let token = core.card_token(r#"{
+            "card_number": "4811111111111114",
+            "card_exp_month": "12",
+            "card_exp_year": "2030",
+            "card_cvv": "123",
+            "client_key": "SB-Mid-client-MOCK"
+        }"#).unwrap();
+
+        let charge = core.charge(&json!({
+            "payment_type": "credit_card",
+            "transaction_details": {"order_id": "order-cc", "gross_amount": 20000},
+            "credit_card": {"token_id": token["token_id"], "type": "authorize"}
+        }).to_string()).unwrap();
+        assert_eq!(charge["transaction_status"], "authorize");
+
+        let capture = core.capture_typed(&json!({
+            "transaction_id": charge["transaction_id"],
+            "gross_amount": 20000
+        }).to_string()).unwrap();
+        assert_eq!(capture.transaction_status, Some(TransactionStatus::Capture));
+        assert_eq!(capture.masked_card.as_deref(), Some("481111-1114"));
+    }
+
+    #[test]
+    fn invalid_card_number() {
+        let mock = Arc::new(MockMidtrans::new(SERVER_KEY.to_string()).build());
+        let core = core_api(&mock);
+        for card_number in ["4éééééé1114", "48111111111a1114", "4811"] {
+            let parameters = json!({
+                "card_number": card_number,
+                "card_exp_month": "12",
+                "card_exp_year": "2030",
+                "client_key": "SB-Mid-client-MOCK"
+            }).to_string();
+            assert_eq!(status_code(core.card_token(&parameters)), 400, "{}", card_number);
+            assert_eq!(status_code(core.card_register(&parameters)), 400, "{}", card_number);
+        }
+    }
+
+    #[test]
+    fn snap_and_subscription() {
+        let mock = Arc::new(MockMidtrans::new(SERVER_KEY.to_string()).build());
+        let snap = Snap::new(false, SERVER_KEY.to_string())
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let transaction = snap.create_transaction_typed(r#"{
+            "transaction_details": {"order_id": "order-snap", "gross_amount": 200000}
+        }"#).unwrap();
+        assert!(transaction.redirect_url.ends_with(&transaction.token));
+        assert_eq!(snap.status("order-snap".to_string()).unwrap()["transaction_status"], "pending");
+
+        let core = core_api(&mock);
+        let subscription = core.create_subscription_typed(r#"{
+            "name": "MONTHLY",
+            "amount": "14000",
+            "currency": "IDR",
+            "payment_type": "credit_card",
+            "token": "481111-1114-token",
+            "schedule": {"interval": 1, "interval_unit": "month"}
+        }"#).unwrap();
+        let id = subscription.id.unwrap();
+        core.disable_subscription(&id).unwrap();
+        let subscription = core.get_subscription_typed(&id).unwrap();
+        assert_eq!(subscription.status.as_deref(), Some("inactive"));
+        assert_eq!(status_code(core.get_subscription("unknown")), 404);
+    }
+
+    /// Minimal HTTP endpoint forwarding every request body to a channel
+    fn notification_receiver() -> (String, mpsc::Receiver<HashMap<String, Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/notification", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let _ = sender.send(serde_json::from_slice(&body).unwrap());
+                let mut stream = reader.into_inner();
+                stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+            }
+        });
+        (url, receiver)
+    }
+
+    #[test]
+    fn http_server_and_notifications() {
+        let (notification_url, notifications) = notification_receiver();
+        let server = MockServer::start(
+            MockMidtrans::new(SERVER_KEY.to_string())
+                .notification_url(notification_url)
+                .build()
+        ).unwrap();
+
+        let core = CoreApi::new(false, SERVER_KEY.to_string())
+            .environment(server.environment())
+            .build()
+            .unwrap();
+        let charge = core.charge(&bank_transfer("order-http")).unwrap();
+        assert_eq!(charge["status_code"], "201");
+
+        let pending = notifications.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(pending["transaction_status"], "pending");
+
+        assert_eq!(server.midtrans().settle("order-http").unwrap(), Some(200));
+        let settlement = notifications.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(settlement["transaction_status"], "settlement");
+        assert!(signature::verify_notification(&settlement, SERVER_KEY).is_ok());
+    }
+}
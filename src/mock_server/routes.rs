@@ -0,0 +1,575 @@
+//! Mock endpoints of Core API, Snap and transaction management
+
+use std::collections::HashMap;
+use reqwest::Method;
+use serde_json::{json, Map, Value};
+use crate::models::{TransactionStatus, FraudStatus};
+use super::state::{MockState, MockTransaction, format_amount, now};
+
+/// Response of a mock endpoint: HTTP status code and JSON body
+pub(crate) type MockResponse = (u16, Value);
+
+/// Route a request to its endpoint. `origin` is the scheme and host the
+/// request was sent to, used to build redirect urls.
+pub(crate) fn route(
+    state: &mut MockState,
+    method: &Method,
+    origin: &str,
+    segments: &[&str],
+    query: &HashMap<String, String>,
+    body: &Map<String, Value>
+) -> MockResponse {
+    match (method.as_str(), segments) {
+        ("POST", ["v2", "charge"]) => charge(state, body),
+        ("POST", ["v2", "capture"]) => capture(state, body),
+        ("GET", ["v2", "token"]) => card_token(query),
+        ("GET", ["v2", "card", "register"]) => card_register(query),
+        ("GET", ["v2", "point_inquiry", _]) => point_inquiry(),
+        ("GET", ["v2", id, "status"]) => status(state, id),
+        ("GET", ["v2", id, "status", "b2b"]) => status_b2b(state, id),
+        ("POST", ["v2", id, "approve"]) => approve(state, id),
+        ("POST", ["v2", id, "deny"]) => deny(state, id),
+        ("POST", ["v2", id, "cancel"]) => cancel(state, id),
+        ("POST", ["v2", id, "expire"]) => expire(state, id),
+        ("POST", ["v2", id, "refund"]) => refund(state, id, body, false),
+        ("POST", ["v2", id, "refund", "online", "direct"]) => refund(state, id, body, true),
+        ("POST", ["v1", "subscriptions"]) => create_subscription(state, body),
+        ("GET", ["v1", "subscriptions", id]) => get_subscription(state, id),
+        ("PATCH", ["v1", "subscriptions", id]) => update_subscription(state, id, body),
+        ("POST", ["v1", "subscriptions", id, "disable"]) => set_subscription_status(state, id, "inactive"),
+        ("POST", ["v1", "subscriptions", id, "enable"]) => set_subscription_status(state, id, "active"),
+        ("POST", ["v2", "pay", "account"]) => link_payment_account(state, body),
+        ("GET", ["v2", "pay", "account", id]) => get_payment_account(state, id),
+        ("POST", ["v2", "pay", "account", id, "unbind"]) => unlink_payment_account(state, id),
+        ("POST", ["snap", "v1", "transactions"]) => create_snap_transaction(state, origin, body),
+        _ => (404, json!({
+            "status_code": "404",
+            "status_message": "The requested resource is not found"
+        }))
+    }
+}
+
+pub(crate) fn unauthorized() -> MockResponse {
+    (401, json!({
+        "status_code": "401",
+        "status_message": "Access denied due to unauthorized transaction, please check client or server key",
+        "id": uuid::Uuid::new_v4().to_string()
+    }))
+}
+
+fn validation_error(messages: Vec<String>) -> MockResponse {
+    (400, json!({
+        "status_code": "400",
+        "status_message": "One or more parameters in the payload is invalid.",
+        "validation_messages": messages
+    }))
+}
+
+fn transaction_not_found() -> MockResponse {
+    (404, json!({
+        "status_code": "404",
+        "status_message": "Transaction doesn't exist.",
+        "id": uuid::Uuid::new_v4().to_string()
+    }))
+}
+
+fn cannot_modify() -> MockResponse {
+    (412, json!({
+        "status_code": "412",
+        "status_message": "Merchant cannot modify the status of the transaction",
+        "id": uuid::Uuid::new_v4().to_string()
+    }))
+}
+
+/// `order_id` and `gross_amount` of `transaction_details`
+fn transaction_details(body: &Map<String, Value>) -> Result<(String, f64), Vec<String>> {
+    let details = body.get("transaction_details").and_then(Value::as_object);
+    let order_id = details
+        .and_then(|details| details.get("order_id"))
+        .and_then(Value::as_str)
+        .filter(|order_id| !order_id.is_empty());
+    let gross_amount = details
+        .and_then(|details| details.get("gross_amount"))
+        .and_then(amount);
+
+    let mut messages = vec![];
+    if order_id.is_none() {
+        messages.push("transaction_details.order_id is required".to_string());
+    }
+    match gross_amount {
+        None => messages.push("transaction_details.gross_amount is required".to_string()),
+        Some(amount) if amount < 0.01 => messages.push(
+            "transaction_details.gross_amount must be greater than or equal to 0.01".to_string()
+        ),
+        _ => {}
+    }
+    match (order_id, gross_amount, messages.is_empty()) {
+        (Some(order_id), Some(gross_amount), true) => Ok((order_id.to_string(), gross_amount)),
+        _ => Err(messages)
+    }
+}
+
+fn amount(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(number) => number.parse().ok(),
+        _ => None
+    }
+}
+
+fn charge(state: &mut MockState, body: &Map<String, Value>) -> MockResponse {
+    let (order_id, gross_amount) = match transaction_details(body) {
+        Ok(details) => details,
+        Err(messages) => return validation_error(messages)
+    };
+    let payment_type = match body.get("payment_type").and_then(Value::as_str) {
+        Some(payment_type) => payment_type,
+        None => return validation_error(vec!["payment_type is required".to_string()])
+    };
+    if state.find(&order_id).is_some() {
+        return (406, json!({
+            "status_code": "406",
+            "status_message": "The request could not be completed due to a conflict with the current state of the target resource, please try again",
+            "id": uuid::Uuid::new_v4().to_string()
+        }))
+    }
+
+    let mut transaction = MockTransaction::new(order_id, gross_amount, Some(payment_type.to_string()));
+    let transaction_id = transaction.id();
+    let mut details = Map::new();
+    // va numbers, bill keys and payment codes are derived from the transaction id
+    let number = |len: usize| transaction_id.bytes()
+        .filter(u8::is_ascii_digit)
+        .chain(std::iter::repeat(b'0'))
+        .take(len)
+        .map(char::from)
+        .collect::<String>();
+
+    let message = match payment_type {
+        "credit_card" => {
+            let credit_card = body.get("credit_card").and_then(Value::as_object);
+            let token_id = credit_card.and_then(|card| card.get("token_id")).and_then(Value::as_str);
+            let token_id = match token_id {
+                Some(token_id) => token_id,
+                None => return validation_error(vec!["credit_card.token_id is required".to_string()])
+            };
+            details.insert("masked_card".to_string(), json!(token_id.get(..11).unwrap_or("481111-1114")));
+            details.insert("bank".to_string(), json!("bni"));
+            details.insert("card_type".to_string(), json!("credit"));
+            details.insert("channel_response_code".to_string(), json!("00"));
+            details.insert("channel_response_message".to_string(), json!("Approved"));
+            details.insert("approval_code".to_string(), json!(number(13)));
+
+            let authentication = credit_card
+                .and_then(|card| card.get("authentication"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let pre_auth = credit_card
+                .and_then(|card| card.get("type"))
+                .and_then(Value::as_str) == Some("authorize");
+            if authentication {
+                details.insert("redirect_url".to_string(), json!(format!(
+                    "https://api.sandbox.midtrans.com/v2/token/rba/redirect/{}", transaction_id
+                )));
+            } else if pre_auth {
+                transaction.status = TransactionStatus::Authorize;
+                transaction.fraud_status = Some(FraudStatus::Accept);
+            } else {
+                transaction.status = TransactionStatus::Capture;
+                transaction.fraud_status = Some(FraudStatus::Accept);
+            }
+            "Credit Card"
+        },
+        "bank_transfer" => {
+            let bank = body.get("bank_transfer")
+                .and_then(|bank_transfer| bank_transfer.get("bank"))
+                .and_then(Value::as_str);
+            match bank {
+                Some("permata") => {
+                    details.insert("permata_va_number".to_string(), json!(number(16)));
+                },
+                Some(bank @ ("bca" | "bni" | "bri" | "cimb")) => {
+                    details.insert("va_numbers".to_string(), json!([{"bank": bank, "va_number": number(11)}]));
+                },
+                _ => return validation_error(vec!["bank_transfer.bank is not supported".to_string()])
+            }
+            "Bank Transfer"
+        },
+        "echannel" => {
+            details.insert("bill_key".to_string(), json!(number(12)));
+            details.insert("biller_code".to_string(), json!("70012"));
+            "Mandiri Bill"
+        },
+        "gopay" | "shopeepay" | "qris" => {
+            let base = format!("https://api.sandbox.midtrans.com/v2/{}/{}", payment_type, transaction_id);
+            let mut actions = vec![
+                json!({"name": "generate-qr-code", "method": "GET", "url": format!("{}/qr-code", base)}),
+                json!({"name": "deeplink-redirect", "method": "GET", "url": format!("{}/deeplink", base)})
+            ];
+            if payment_type == "qris" {
+                actions.truncate(1);
+                details.insert("acquirer".to_string(), json!("gopay"));
+                details.insert("qr_string".to_string(), json!(format!("00020101021226620014COM.GO-JEK.WWW{}", number(12))));
+            }
+            details.insert("actions".to_string(), json!(actions));
+            match payment_type {
+                "gopay" => "GoPay",
+                "shopeepay" => "ShopeePay",
+                _ => "QRIS"
+            }
+        },
+        "cstore" => {
+            let store = body.get("cstore")
+                .and_then(|cstore| cstore.get("store"))
+                .and_then(Value::as_str)
+                .unwrap_or("indomaret");
+            details.insert("payment_code".to_string(), json!(number(14)));
+            details.insert("store".to_string(), json!(store));
+            "Cstore"
+        },
+        "akulaku" | "kredivo" => {
+            details.insert("redirect_url".to_string(), json!(format!(
+                "https://api.sandbox.midtrans.com/v2/{}/redirect/{}", payment_type, transaction_id
+            )));
+            "Paylater"
+        },
+        _ => return validation_error(vec![format!("payment_type {} is not supported", payment_type)])
+    };
+    transaction.details = details;
+
+    let mut response = transaction.to_json();
+    let status_code = match transaction.status {
+        TransactionStatus::Pending => "201",
+        _ => "200"
+    };
+    response.insert("status_code".to_string(), json!(status_code));
+    response.insert("status_message".to_string(), json!(format!("Success, {} transaction is successful", message)));
+    state.insert(transaction);
+    state.notify(transaction_id);
+    (200, Value::Object(response))
+}
+
+fn capture(state: &mut MockState, body: &Map<String, Value>) -> MockResponse {
+    let transaction_id = body.get("transaction_id").and_then(Value::as_str).unwrap_or_default();
+    transition(state, transaction_id, TransactionStatus::Capture, "200", "Success, Credit Card capture transaction is successful")
+}
+
+/// Change the status of a transaction through the API, rejecting illegal transitions
+fn transition(state: &mut MockState, id: &str, next: TransactionStatus, status_code: &str, message: &str) -> MockResponse {
+    let transaction = match state.find_mut(id) {
+        Some(transaction) => transaction,
+        None => return transaction_not_found()
+    };
+    if !transaction.status.can_transition_to(next) {
+        return cannot_modify()
+    }
+    transaction.status = next;
+    let mut response = transaction.to_json();
+    response.insert("status_code".to_string(), json!(status_code));
+    response.insert("status_message".to_string(), json!(message));
+    let notification = transaction.id();
+    state.notify(notification);
+    (200, Value::Object(response))
+}
+
+fn status(state: &MockState, id: &str) -> MockResponse {
+    match state.find(id) {
+        Some(transaction) => (200, Value::Object(transaction.to_status_json(&state.server_key))),
+        None => transaction_not_found()
+    }
+}
+
+fn status_b2b(state: &MockState, id: &str) -> MockResponse {
+    match state.find(id) {
+        Some(transaction) => (200, json!({
+            "status_code": "200",
+            "status_message": "Success, transaction is found",
+            "transactions": [transaction.to_status_json(&state.server_key)]
+        })),
+        None => transaction_not_found()
+    }
+}
+
+fn approve(state: &mut MockState, id: &str) -> MockResponse {
+    let transaction = match state.find_mut(id) {
+        Some(transaction) => transaction,
+        None => return transaction_not_found()
+    };
+    if transaction.status != TransactionStatus::Capture || transaction.fraud_status != Some(FraudStatus::Challenge) {
+        return cannot_modify()
+    }
+    transaction.fraud_status = Some(FraudStatus::Accept);
+    let mut response = transaction.to_json();
+    response.insert("status_code".to_string(), json!("200"));
+    response.insert("status_message".to_string(), json!("Success, transaction is approved"));
+    let notification = transaction.id();
+    state.notify(notification);
+    (200, Value::Object(response))
+}
+
+fn deny(state: &mut MockState, id: &str) -> MockResponse {
+    let challenged = state.find(id)
+        .map(|transaction| transaction.fraud_status == Some(FraudStatus::Challenge));
+    match challenged {
+        None => transaction_not_found(),
+        Some(false) => cannot_modify(),
+        Some(true) => transition(state, id, TransactionStatus::Deny, "200", "Success, transaction is denied")
+    }
+}
+
+fn cancel(state: &mut MockState, id: &str) -> MockResponse {
+    transition(state, id, TransactionStatus::Cancel, "200", "Success, transaction is canceled")
+}
+
+fn expire(state: &mut MockState, id: &str) -> MockResponse {
+    // Midtrans answers a successful expire with status_code 407
+    match transition(state, id, TransactionStatus::Expire, "407", "Success, transaction is expired") {
+        (200, body) => (407, body),
+        response => response
+    }
+}
+
+fn refund(state: &mut MockState, id: &str, body: &Map<String, Value>, direct: bool) -> MockResponse {
+    let refund_chargeback_id = state.next_refund_id();
+    let transaction = match state.find_mut(id) {
+        Some(transaction) => transaction,
+        None => return transaction_not_found()
+    };
+    let refundable = matches!(
+        transaction.status,
+        TransactionStatus::Settlement | TransactionStatus::PartialRefund
+    );
+    if !refundable {
+        return cannot_modify()
+    }
+
+    let remaining = transaction.gross_amount - transaction.refunded_amount();
+    let amount = body.get("amount").and_then(amount).unwrap_or(remaining);
+    if amount <= 0.0 || amount > remaining {
+        return validation_error(vec!["amount must not exceed the remaining refundable amount".to_string()])
+    }
+    let refund_key = body.get("refund_key")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    transaction.refunds.push(json!({
+        "refund_chargeback_id": refund_chargeback_id,
+        "refund_amount": format_amount(amount),
+        "created_at": now(),
+        "reason": body.get("reason").cloned().unwrap_or(Value::Null),
+        "refund_key": refund_key,
+        "refund_method": if direct { "online" } else { "offline" }
+    }));
+    transaction.status = match amount < remaining {
+        true => TransactionStatus::PartialRefund,
+        false => TransactionStatus::Refund
+    };
+
+    let mut response = transaction.to_json();
+    response.insert("status_code".to_string(), json!("200"));
+    response.insert("status_message".to_string(), json!("Success, refund request is approved"));
+    response.insert("refund_chargeback_id".to_string(), json!(refund_chargeback_id));
+    response.insert("refund_amount".to_string(), json!(format_amount(amount)));
+    response.insert("refund_key".to_string(), json!(refund_key));
+    let notification = transaction.id();
+    state.notify(notification);
+    (200, Value::Object(response))
+}
+
+/// `card_number` of the query without spaces, `None` unless it is 12 to 19 ASCII digits
+fn card_number(query: &HashMap<String, String>) -> Option<String> {
+    let card_number: String = query.get("card_number")?.chars().filter(|c| *c != ' ').collect();
+    match (12..=19).contains(&card_number.len()) && card_number.bytes().all(|b| b.is_ascii_digit()) {
+        true => Some(card_number),
+        false => None
+    }
+}
+
+fn card_token(query: &HashMap<String, String>) -> MockResponse {
+    match card_number(query) {
+        Some(card_number) => {
+            let masked = format!("{}-{}", &card_number[..6], &card_number[card_number.len() - 4..]);
+            (200, json!({
+                "status_code": "200",
+                "status_message": "OK, success request new token",
+                "token_id": format!("{}-{}", masked, uuid::Uuid::new_v4()),
+                "hash": format!("{}-mami", masked)
+            }))
+        },
+        None => validation_error(vec!["card_number is invalid".to_string()])
+    }
+}
+
+fn card_register(query: &HashMap<String, String>) -> MockResponse {
+    match card_number(query) {
+        Some(card_number) => (200, json!({
+            "status_code": "200",
+            "saved_token_id": format!("{}{}", &card_number[..6], uuid::Uuid::new_v4().simple()),
+            "transaction_id": uuid::Uuid::new_v4().to_string(),
+            "masked_card": format!("{}-{}", &card_number[..6], &card_number[card_number.len() - 4..])
+        })),
+        None => validation_error(vec!["card_number is invalid".to_string()])
+    }
+}
+
+fn point_inquiry() -> MockResponse {
+    (200, json!({
+        "status_code": "200",
+        "status_message": "Success, Credit Card Point inquiry is successful",
+        "point_balance_amount": "0.00",
+        "transaction_time": now()
+    }))
+}
+
+fn create_subscription(state: &mut MockState, body: &Map<String, Value>) -> MockResponse {
+    let mut messages = vec![];
+    for field in ["name", "amount", "currency", "payment_type", "token"] {
+        if !body.contains_key(field) {
+            messages.push(format!("{} is required", field));
+        }
+    }
+    if body.get("amount").and_then(amount).is_some_and(|amount| amount < 0.01) {
+        messages.push("amount must be greater than or equal to 0.01".to_string());
+    }
+    let schedule = body.get("schedule").and_then(Value::as_object);
+    if schedule.and_then(|schedule| schedule.get("interval")).is_none() {
+        messages.push("schedule.interval is required".to_string());
+    }
+    if !messages.is_empty() {
+        return validation_error(messages)
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let mut subscription = body.clone();
+    subscription.insert("id".to_string(), json!(id));
+    subscription.insert("status".to_string(), json!("active"));
+    subscription.insert("created_at".to_string(), json!(now()));
+    if let Some(Value::Object(schedule)) = subscription.get_mut("schedule") {
+        schedule.entry("current_interval").or_insert(json!(0));
+        schedule.entry("next_execution_at").or_insert(json!(now()));
+    }
+    state.subscriptions.insert(id, subscription.clone());
+    (200, Value::Object(subscription))
+}
+
+fn subscription_not_found() -> MockResponse {
+    (404, json!({"status_code": "404", "status_message": "Subscription doesn't exist."}))
+}
+
+fn get_subscription(state: &MockState, id: &str) -> MockResponse {
+    match state.subscriptions.get(id) {
+        Some(subscription) => (200, Value::Object(subscription.clone())),
+        None => subscription_not_found()
+    }
+}
+
+fn update_subscription(state: &mut MockState, id: &str, body: &Map<String, Value>) -> MockResponse {
+    match state.subscriptions.get_mut(id) {
+        Some(subscription) => {
+            for (key, value) in body {
+                subscription.insert(key.clone(), value.clone());
+            }
+            (200, json!({"status_code": "200", "status_message": "Subscription is updated."}))
+        },
+        None => subscription_not_found()
+    }
+}
+
+fn set_subscription_status(state: &mut MockState, id: &str, status: &str) -> MockResponse {
+    match state.subscriptions.get_mut(id) {
+        Some(subscription) => {
+            subscription.insert("status".to_string(), json!(status));
+            (200, json!({"status_code": "200", "status_message": "Subscription is updated."}))
+        },
+        None => subscription_not_found()
+    }
+}
+
+fn link_payment_account(state: &mut MockState, body: &Map<String, Value>) -> MockResponse {
+    let payment_type = body.get("payment_type").and_then(Value::as_str).unwrap_or_default();
+    let phone_number = body.get("gopay_partner")
+        .and_then(|partner| partner.get("phone_number"))
+        .and_then(Value::as_str);
+    if payment_type != "gopay" || phone_number.is_none() {
+        return validation_error(vec!["gopay_partner.phone_number is required".to_string()])
+    }
+
+    let account_id = uuid::Uuid::new_v4().to_string();
+    let account = json!({
+        "payment_type": "gopay",
+        "account_id": account_id,
+        "account_status": "PENDING",
+        "actions": [{
+            "name": "activation-deeplink",
+            "method": "GET",
+            "url": format!("https://api.sandbox.midtrans.com/v2/pay/account/gpar_{}/link", account_id)
+        }],
+        "metadata": {"reference_id": uuid::Uuid::new_v4().to_string()}
+    });
+    let Value::Object(account) = account else { unreachable!() };
+    state.pay_accounts.insert(account_id, account.clone());
+
+    let mut response = account;
+    response.insert("status_code".to_string(), json!("201"));
+    (200, Value::Object(response))
+}
+
+fn pay_account_not_found() -> MockResponse {
+    (404, json!({
+        "status_code": "404",
+        "status_message": "Account doesn't exist.",
+        "id": uuid::Uuid::new_v4().to_string()
+    }))
+}
+
+fn get_payment_account(state: &MockState, id: &str) -> MockResponse {
+    match state.pay_accounts.get(id) {
+        Some(account) => {
+            let status_code = match account["account_status"] == "PENDING" {
+                true => "201",
+                false => "200"
+            };
+            let mut response = account.clone();
+            response.insert("status_code".to_string(), json!(status_code));
+            (200, Value::Object(response))
+        },
+        None => pay_account_not_found()
+    }
+}
+
+fn unlink_payment_account(state: &mut MockState, id: &str) -> MockResponse {
+    match state.pay_accounts.get_mut(id) {
+        Some(account) if account["account_status"] == "ENABLED" => {
+            account.insert("account_status".to_string(), json!("DISABLED"));
+            let mut response = account.clone();
+            response.insert("status_code".to_string(), json!("204"));
+            response.insert("channel_response_code".to_string(), json!("0"));
+            response.insert("channel_response_message".to_string(), json!("Process service request successfully."));
+            (200, Value::Object(response))
+        },
+        Some(_) => (412, json!({
+            "status_code": "412",
+            "status_message": "Account status cannot be updated.",
+            "id": uuid::Uuid::new_v4().to_string()
+        })),
+        None => pay_account_not_found()
+    }
+}
+
+fn create_snap_transaction(state: &mut MockState, origin: &str, body: &Map<String, Value>) -> MockResponse {
+    let (order_id, gross_amount) = match transaction_details(body) {
+        Ok(details) => details,
+        Err(messages) => return (400, json!({"error_messages": messages}))
+    };
+    if state.find(&order_id).is_some() {
+        return (400, json!({"error_messages": ["transaction_details.order_id has already been taken"]}))
+    }
+
+    let token = uuid::Uuid::new_v4().to_string();
+    state.insert(MockTransaction::new(order_id, gross_amount, None));
+    (201, json!({
+        "token": token,
+        "redirect_url": format!("{}/snap/v4/redirection/{}", origin, token)
+    }))
+}
@@ -0,0 +1,99 @@
+//! HTTP Mock Server
+
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
+use crate::{Environment, MidtransError};
+use crate::transport::TransportRequest;
+use super::MockMidtrans;
+
+/// [`MockMidtrans`] served over HTTP on a random local port,
+/// stopped when dropped
+pub struct MockServer {
+    url: String,
+    midtrans: Arc<MockMidtrans>,
+    server: Arc<tiny_http::Server>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl MockServer {
+    /// Serve `midtrans` on `127.0.0.1`
+    pub fn start(midtrans: MockMidtrans) -> Result<Self, MidtransError> {
+        let server = tiny_http::Server::http("127.0.0.1:0").map_err(MidtransError::TransportError)?;
+        let port = server.server_addr().to_ip().map(|addr| addr.port()).unwrap_or_default();
+        let url = format!("http://127.0.0.1:{}", port);
+
+        let server = Arc::new(server);
+        let midtrans = Arc::new(midtrans);
+        let handle = {
+            let (server, midtrans, url) = (server.clone(), midtrans.clone(), url.clone());
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    respond(&midtrans, &url, request);
+                }
+            })
+        };
+
+        Ok(Self { url, midtrans, server, handle: Some(handle) })
+    }
+
+    /// Base url of the server, e.g. `http://127.0.0.1:39123`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// [`Environment`] pointing Core API, Snap and Iris at this server
+    pub fn environment(&self) -> Environment {
+        Environment::Custom {
+            core_url: self.url.clone(),
+            snap_url: self.url.clone(),
+            iris_url: format!("{}/iris", self.url)
+        }
+    }
+
+    /// The served [`MockMidtrans`], to drive transaction statuses
+    pub fn midtrans(&self) -> &MockMidtrans {
+        &self.midtrans
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn respond(midtrans: &MockMidtrans, url: &str, mut request: tiny_http::Request) {
+    let mut headers = HeaderMap::new();
+    for header in request.headers() {
+        let name = HeaderName::from_bytes(header.field.as_str().as_bytes());
+        let value = HeaderValue::from_str(header.value.as_str());
+        if let (Ok(name), Ok(value)) = (name, value) {
+            headers.insert(name, value);
+        }
+    }
+
+    let mut body = String::new();
+    let _ = request.as_reader().read_to_string(&mut body);
+    let method = Method::from_bytes(request.method().as_str().as_bytes()).unwrap_or(Method::GET);
+
+    let response = midtrans.handle(&TransportRequest {
+        method,
+        url: format!("{}{}", url, request.url()),
+        headers,
+        body: Some(body),
+        timeout: None
+    });
+
+    let content_type = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("valid header");
+    let _ = request.respond(
+        tiny_http::Response::from_string(response.body)
+            .with_status_code(response.status_code)
+            .with_header(content_type)
+    );
+}
@@ -0,0 +1,171 @@
+//! In-memory state of the mock
+
+use std::collections::HashMap;
+use serde_json::{json, Map, Value};
+use crate::datetime::WibDateTime;
+use crate::models::{TransactionStatus, FraudStatus};
+use crate::signature;
+
+pub(crate) struct MockTransaction {
+    pub transaction_id: String,
+    pub order_id: String,
+    pub gross_amount: f64,
+    pub payment_type: Option<String>,
+    pub transaction_time: String,
+    pub settlement_time: Option<String>,
+    pub status: TransactionStatus,
+    pub fraud_status: Option<FraudStatus>,
+    /// Payment type specific fields, e.g. `va_numbers`
+    pub details: Map<String, Value>,
+    pub refunds: Vec<Value>
+}
+
+impl MockTransaction {
+    pub fn new(order_id: String, gross_amount: f64, payment_type: Option<String>) -> Self {
+        Self {
+            transaction_id: uuid::Uuid::new_v4().to_string(),
+            order_id,
+            gross_amount,
+            payment_type,
+            transaction_time: now(),
+            settlement_time: None,
+            status: TransactionStatus::Pending,
+            fraud_status: None,
+            details: Map::new(),
+            refunds: vec![]
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.transaction_id.clone()
+    }
+
+    pub fn refunded_amount(&self) -> f64 {
+        self.refunds.iter()
+            .filter_map(|refund| refund["refund_amount"].as_str())
+            .filter_map(|amount| amount.parse::<f64>().ok())
+            .sum()
+    }
+
+    /// Transaction fields shared by charge, action and status responses
+    pub fn to_json(&self) -> Map<String, Value> {
+        let mut body = Map::new();
+        body.insert("transaction_id".to_string(), json!(self.transaction_id));
+        body.insert("order_id".to_string(), json!(self.order_id));
+        body.insert("merchant_id".to_string(), json!("M000000000"));
+        body.insert("gross_amount".to_string(), json!(format_amount(self.gross_amount)));
+        body.insert("currency".to_string(), json!("IDR"));
+        if let Some(payment_type) = &self.payment_type {
+            body.insert("payment_type".to_string(), json!(payment_type));
+        }
+        body.insert("transaction_time".to_string(), json!(self.transaction_time));
+        body.insert("transaction_status".to_string(), json!(self.status));
+        if let Some(fraud_status) = self.fraud_status {
+            body.insert("fraud_status".to_string(), json!(fraud_status));
+        }
+        if let Some(settlement_time) = &self.settlement_time {
+            body.insert("settlement_time".to_string(), json!(settlement_time));
+        }
+        body.extend(self.details.clone());
+        body
+    }
+
+    /// Body of `/status` responses and HTTP notifications
+    pub fn to_status_json(&self, server_key: &str) -> Map<String, Value> {
+        let status_code = match self.status {
+            TransactionStatus::Pending => "201",
+            TransactionStatus::Deny | TransactionStatus::Failure => "202",
+            TransactionStatus::Expire => "407",
+            _ => "200"
+        };
+        let mut body = self.to_json();
+        body.insert("status_code".to_string(), json!(status_code));
+        body.insert("status_message".to_string(), json!("Success, transaction is found"));
+        body.insert("signature_key".to_string(), json!(signature::signature_key(
+            &self.order_id,
+            status_code,
+            &format_amount(self.gross_amount),
+            server_key
+        )));
+        if !self.refunds.is_empty() {
+            body.insert("refund_amount".to_string(), json!(format_amount(self.refunded_amount())));
+            body.insert("refunds".to_string(), json!(self.refunds));
+        }
+        body
+    }
+}
+
+pub(crate) struct MockState {
+    pub server_key: String,
+    transactions: HashMap<String, MockTransaction>,
+    order_ids: HashMap<String, String>,
+    pub subscriptions: HashMap<String, Map<String, Value>>,
+    pub pay_accounts: HashMap<String, Map<String, Value>>,
+    refund_counter: u64,
+    notifications: Vec<String>
+}
+
+impl MockState {
+    pub fn new(server_key: String) -> Self {
+        Self {
+            server_key,
+            transactions: HashMap::new(),
+            order_ids: HashMap::new(),
+            subscriptions: HashMap::new(),
+            pay_accounts: HashMap::new(),
+            refund_counter: 0,
+            notifications: vec![]
+        }
+    }
+
+    pub fn insert(&mut self, transaction: MockTransaction) {
+        self.order_ids.insert(transaction.order_id.clone(), transaction.transaction_id.clone());
+        self.transactions.insert(transaction.transaction_id.clone(), transaction);
+    }
+
+    /// Find a transaction by `transaction_id` or `order_id`
+    pub fn find(&self, id: &str) -> Option<&MockTransaction> {
+        let transaction_id = self.order_ids.get(id).map(String::as_str).unwrap_or(id);
+        self.transactions.get(transaction_id)
+    }
+
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut MockTransaction> {
+        let transaction_id = self.order_ids.get(id).cloned().unwrap_or_else(|| id.to_string());
+        self.transactions.get_mut(&transaction_id)
+    }
+
+    pub fn next_refund_id(&mut self) -> u64 {
+        self.refund_counter += 1;
+        self.refund_counter
+    }
+
+    /// Queue a notification of the transaction, sent once the state is unlocked
+    pub fn notify(&mut self, transaction_id: String) {
+        self.notifications.push(transaction_id);
+    }
+
+    pub fn take_notifications(&mut self) -> Vec<Map<String, Value>> {
+        let notifications = std::mem::take(&mut self.notifications);
+        notifications.iter()
+            .filter_map(|id| self.notification(id))
+            .collect()
+    }
+
+    /// HTTP notification body of a transaction
+    pub fn notification(&self, id: &str) -> Option<Map<String, Value>> {
+        self.find(id).map(|transaction| {
+            let mut body = transaction.to_status_json(&self.server_key);
+            body.insert("status_message".to_string(), json!("midtrans payment notification"));
+            body
+        })
+    }
+}
+
+pub(crate) fn format_amount(amount: f64) -> String {
+    format!("{:.2}", amount)
+}
+
+/// Current time in Western Indonesia Time (UTC+7), formatted like Midtrans
+pub(crate) fn now() -> String {
+    WibDateTime::now().to_midtrans_string()
+}
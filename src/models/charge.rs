@@ -0,0 +1,417 @@
+//! Charge Request Models
+//!
+//! (more params detail refer to: <https://api-docs.midtrans.com/#charge-features>)
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Typed parameters of `/v2/charge` API call, used by `CoreApi::charge_typed`
+#[derive(Debug, Clone, Serialize)]
+pub struct ChargeRequest {
+    #[serde(flatten)]
+    pub payment_method: PaymentMethod,
+    pub transaction_details: TransactionDetails,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_details: Option<Vec<ItemDetails>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_details: Option<CustomerDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_expiry: Option<CustomExpiry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_field1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_field2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_field3: Option<String>
+}
+
+impl ChargeRequest {
+    pub fn new(payment_method: PaymentMethod, order_id: String, gross_amount: i64) -> Self {
+        Self {
+            payment_method,
+            transaction_details: TransactionDetails { order_id, gross_amount },
+            item_details: None,
+            customer_details: None,
+            custom_expiry: None,
+            metadata: None,
+            custom_field1: None,
+            custom_field2: None,
+            custom_field3: None
+        }
+    }
+}
+
+/// Payment method of the charge, serialized as `payment_type` and its
+/// payment specific object, e.g. `{"payment_type": "gopay", "gopay": {...}}`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "payment_type", rename_all = "snake_case")]
+pub enum PaymentMethod {
+    CreditCard { credit_card: CreditCard },
+    /// BCA, BNI, BRI, CIMB and Permata virtual account
+    BankTransfer { bank_transfer: BankTransfer },
+    /// Mandiri bill payment
+    Echannel { echannel: Echannel },
+    Gopay {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gopay: Option<Gopay>
+    },
+    Shopeepay {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shopeepay: Option<Shopeepay>
+    },
+    Qris {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        qris: Option<Qris>
+    },
+    /// Indomaret and Alfamart
+    Cstore { cstore: Cstore },
+    Akulaku,
+    Kredivo
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransactionDetails {
+    pub order_id: String,
+    pub gross_amount: i64
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ItemDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub price: i64,
+    pub quantity: u32,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brand: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merchant_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CustomerDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billing_address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shipping_address: Option<Address>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Address {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomExpiry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_time: Option<String>,
+    pub expiry_duration: u32,
+    pub unit: ExpiryUnit
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpiryUnit {
+    Second,
+    Minute,
+    Hour,
+    Day
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CreditCard {
+    pub token_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installment_term: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bins: Option<Vec<String>>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<CreditCardTransactionType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_token_id: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authentication: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>
+}
+
+impl CreditCard {
+    pub fn new(token_id: String) -> Self {
+        Self { token_id, ..Default::default() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditCardTransactionType {
+    Authorize,
+    AuthorizeCapture
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bank {
+    Bca,
+    Bni,
+    Bri,
+    Cimb,
+    Permata
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BankTransfer {
+    pub bank: Bank,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub va_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub free_text: Option<FreeText>,
+    /// BCA specific options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bca: Option<BcaOptions>,
+    /// Permata specific options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permata: Option<PermataOptions>
+}
+
+impl BankTransfer {
+    pub fn new(bank: Bank) -> Self {
+        Self { bank, va_number: None, free_text: None, bca: None, permata: None }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FreeText {
+    pub inquiry: Vec<FreeTextItem>,
+    pub payment: Vec<FreeTextItem>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FreeTextItem {
+    pub id: String,
+    pub en: String
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BcaOptions {
+    pub sub_company_code: String
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PermataOptions {
+    pub recipient_name: String
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Echannel {
+    pub bill_info1: String,
+    pub bill_info2: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bill_info3: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bill_info4: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bill_info5: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bill_info6: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bill_info7: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bill_info8: Option<String>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Gopay {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_callback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_option_token: Option<String>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Shopeepay {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Qris {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acquirer: Option<QrisAcquirer>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrisAcquirer {
+    Gopay,
+    Airpay
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Cstore {
+    pub store: Store,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alfamart_free_text_1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alfamart_free_text_2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alfamart_free_text_3: Option<String>
+}
+
+impl Cstore {
+    pub fn new(store: Store) -> Self {
+        Self {
+            store,
+            message: None,
+            alfamart_free_text_1: None,
+            alfamart_free_text_2: None,
+            alfamart_free_text_3: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Store {
+    Indomaret,
+    Alfamart
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn to_json(payment_method: PaymentMethod) -> Value {
+        let request = ChargeRequest::new(payment_method, "order-1".to_string(), 10000);
+        serde_json::to_value(request).unwrap()
+    }
+
+    #[test]
+    fn credit_card() {
+        let mut credit_card = CreditCard::new("token-1".to_string());
+        credit_card.authentication = Some(true);
+        credit_card.transaction_type = Some(CreditCardTransactionType::AuthorizeCapture);
+        assert_eq!(to_json(PaymentMethod::CreditCard { credit_card }), json!({
+            "payment_type": "credit_card",
+            "transaction_details": {"order_id": "order-1", "gross_amount": 10000},
+            "credit_card": {"token_id": "token-1", "type": "authorize_capture", "authentication": true}
+        }));
+    }
+
+    #[test]
+    fn bank_transfer() {
+        let bank_transfer = BankTransfer::new(Bank::Bca);
+        assert_eq!(to_json(PaymentMethod::BankTransfer { bank_transfer }), json!({
+            "payment_type": "bank_transfer",
+            "transaction_details": {"order_id": "order-1", "gross_amount": 10000},
+            "bank_transfer": {"bank": "bca"}
+        }));
+
+        let mut bank_transfer = BankTransfer::new(Bank::Permata);
+        bank_transfer.permata = Some(PermataOptions { recipient_name: "SUDARSONO".to_string() });
+        assert_eq!(to_json(PaymentMethod::BankTransfer { bank_transfer })["bank_transfer"], json!({
+            "bank": "permata",
+            "permata": {"recipient_name": "SUDARSONO"}
+        }));
+    }
+
+    #[test]
+    fn echannel() {
+        let echannel = Echannel {
+            bill_info1: "Payment:".to_string(),
+            bill_info2: "Online purchase".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(to_json(PaymentMethod::Echannel { echannel })["echannel"], json!({
+            "bill_info1": "Payment:",
+            "bill_info2": "Online purchase"
+        }));
+    }
+
+    #[test]
+    fn e_wallets_and_qris() {
+        let gopay = to_json(PaymentMethod::Gopay { gopay: None });
+        assert_eq!(gopay["payment_type"], "gopay");
+        assert!(gopay.get("gopay").is_none());
+
+        let shopeepay = Shopeepay { callback_url: Some("https://example.com".to_string()) };
+        let shopeepay = to_json(PaymentMethod::Shopeepay { shopeepay: Some(shopeepay) });
+        assert_eq!(shopeepay["shopeepay"], json!({"callback_url": "https://example.com"}));
+
+        let qris = Qris { acquirer: Some(QrisAcquirer::Gopay) };
+        let qris = to_json(PaymentMethod::Qris { qris: Some(qris) });
+        assert_eq!(qris["payment_type"], "qris");
+        assert_eq!(qris["qris"], json!({"acquirer": "gopay"}));
+    }
+
+    #[test]
+    fn cstore() {
+        let cstore = Cstore::new(Store::Alfamart);
+        let cstore = to_json(PaymentMethod::Cstore { cstore });
+        assert_eq!(cstore["payment_type"], "cstore");
+        assert_eq!(cstore["cstore"], json!({"store": "alfamart"}));
+    }
+
+    #[test]
+    fn paylater() {
+        assert_eq!(to_json(PaymentMethod::Akulaku)["payment_type"], "akulaku");
+        assert_eq!(to_json(PaymentMethod::Kredivo)["payment_type"], "kredivo");
+    }
+
+    #[test]
+    fn optional_details() {
+        let mut request = ChargeRequest::new(PaymentMethod::Akulaku, "order-1".to_string(), 10000);
+        request.item_details = Some(vec![ItemDetails {
+            price: 10000,
+            quantity: 1,
+            name: "Midtrans Bear".to_string(),
+            ..Default::default()
+        }]);
+        request.custom_expiry = Some(CustomExpiry {
+            order_time: None,
+            expiry_duration: 60,
+            unit: ExpiryUnit::Minute
+        });
+        let request = serde_json::to_value(request).unwrap();
+        assert_eq!(request["item_details"], json!([{"price": 10000, "quantity": 1, "name": "Midtrans Bear"}]));
+        assert_eq!(request["custom_expiry"], json!({"expiry_duration": 60, "unit": "minute"}));
+        assert!(request.get("customer_details").is_none());
+    }
+}
@@ -0,0 +1,191 @@
+//! Invoice Models
+//!
+//! (more params detail refer to: <https://docs.midtrans.com/reference/invoicing-api>)
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Typed parameters of `/v1/invoices` API call, used by `CoreApi::create_invoice_typed`
+#[derive(Debug, Clone, Serialize)]
+pub struct InvoiceRequest {
+    pub order_id: String,
+    pub invoice_number: String,
+    /// Format `yyyy-MM-dd HH:mm:ss Z`, e.g. `2024-05-15 16:00:00 +0700`
+    pub invoice_date: String,
+    /// Format `yyyy-MM-dd HH:mm:ss Z`, must be after `invoice_date`
+    pub due_date: String,
+    pub customer_details: InvoiceCustomerDetails,
+    pub item_details: Vec<InvoiceItem>,
+    #[serde(flatten)]
+    pub payment_type: InvoicePaymentType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>
+}
+
+impl InvoiceRequest {
+    pub fn new(
+        order_id: String,
+        invoice_number: String,
+        invoice_date: String,
+        due_date: String,
+        customer_details: InvoiceCustomerDetails,
+        payment_type: InvoicePaymentType
+    ) -> Self {
+        Self {
+            order_id,
+            invoice_number,
+            invoice_date,
+            due_date,
+            customer_details,
+            item_details: Vec::new(),
+            payment_type,
+            reference: None,
+            notes: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InvoiceCustomerDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InvoiceItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    pub description: String,
+    pub quantity: u32,
+    pub price: i64
+}
+
+/// How the invoice is paid, serialized as `payment_type` and its payment specific field,
+/// e.g. `{"payment_type": "virtual_account", "virtual_accounts": [...]}`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "payment_type", rename_all = "snake_case")]
+pub enum InvoicePaymentType {
+    PaymentLink {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payment_link: Option<InvoicePaymentLink>
+    },
+    VirtualAccount { virtual_accounts: Vec<InvoiceVirtualAccount> }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InvoicePaymentLink {
+    /// Payment channels shown to the customer, e.g. `credit_card`, `bca_va`, `gopay`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_payments: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_custom_expiry: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<Value>
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InvoiceVirtualAccount {
+    /// Virtual account channel, e.g. `bca_va`, `bni_va`, `bri_va` or `permata_va`
+    pub name: String,
+    /// Custom virtual account number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>
+}
+
+/// Response of `CoreApi::create_invoice`, `CoreApi::get_invoice` and `CoreApi::void_invoice`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct InvoiceResponse {
+    pub id: Option<String>,
+    pub order_id: Option<String>,
+    pub invoice_number: Option<String>,
+    pub status: Option<String>,
+    pub invoice_date: Option<String>,
+    pub due_date: Option<String>,
+    pub payment_type: Option<String>,
+    pub pdf_url: Option<String>,
+    pub payment_link_url: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn request(payment_type: InvoicePaymentType) -> InvoiceRequest {
+        let customer_details = InvoiceCustomerDetails {
+            name: "John Doe".to_string(),
+            email: Some("john@example.com".to_string()),
+            ..Default::default()
+        };
+        let mut request = InvoiceRequest::new(
+            "order-1".to_string(),
+            "INV-001".to_string(),
+            "2024-05-01 16:00:00 +0700".to_string(),
+            "2024-05-15 16:00:00 +0700".to_string(),
+            customer_details,
+            payment_type
+        );
+        request.item_details.push(InvoiceItem {
+            description: "Consulting".to_string(),
+            quantity: 2,
+            price: 500000,
+            ..Default::default()
+        });
+        request
+    }
+
+    #[test]
+    fn virtual_account() {
+        let payment_type = InvoicePaymentType::VirtualAccount {
+            virtual_accounts: vec![InvoiceVirtualAccount { name: "bca_va".to_string(), number: None }]
+        };
+        assert_eq!(serde_json::to_value(request(payment_type)).unwrap(), json!({
+            "order_id": "order-1",
+            "invoice_number": "INV-001",
+            "invoice_date": "2024-05-01 16:00:00 +0700",
+            "due_date": "2024-05-15 16:00:00 +0700",
+            "customer_details": {"name": "John Doe", "email": "john@example.com"},
+            "item_details": [{"description": "Consulting", "quantity": 2, "price": 500000}],
+            "payment_type": "virtual_account",
+            "virtual_accounts": [{"name": "bca_va"}]
+        }));
+    }
+
+    #[test]
+    fn payment_link() {
+        let payment_type = InvoicePaymentType::PaymentLink {
+            payment_link: Some(InvoicePaymentLink {
+                enabled_payments: Some(vec!["gopay".to_string()]),
+                ..Default::default()
+            })
+        };
+        let request = serde_json::to_value(request(payment_type)).unwrap();
+        assert_eq!(request["payment_type"], "payment_link");
+        assert_eq!(request["payment_link"], json!({"enabled_payments": ["gopay"]}));
+
+        let request = serde_json::to_value(self::request(InvoicePaymentType::PaymentLink { payment_link: None })).unwrap();
+        assert!(request.get("payment_link").is_none());
+    }
+
+    #[test]
+    fn response() {
+        let response: InvoiceResponse = serde_json::from_value(json!({
+            "id": "inv-1",
+            "order_id": "order-1",
+            "status": "voided",
+            "gross_amount": 1000000
+        })).unwrap();
+        assert_eq!(response.status.as_deref(), Some("voided"));
+        assert_eq!(response.extra["gross_amount"], 1000000);
+    }
+}
@@ -0,0 +1,222 @@
+//! Transaction Lifecycle
+//!
+//! The documented Midtrans transaction lifecycle as a state machine
+//! (refer to: <https://docs.midtrans.com/docs/https-notification-webhooks#transaction-status>)
+//!
+//! - `pending` → `authorize`, `capture`, `settlement`, `deny`, `cancel`, `expire`, `failure`
+//! - `authorize` → `capture`, `deny`, `cancel`, `expire`
+//! - `capture` → `settlement`, `deny`, `cancel`, refunds and chargebacks
+//! - `settlement` → refunds and chargebacks
+//! - `partial_refund` / `partial_chargeback` → further refunds and chargebacks
+
+use super::notification::{Notification, TransactionNotification};
+use super::response::{TransactionStatus, FraudStatus, TransactionStatusResponse};
+
+impl TransactionStatus {
+    /// Statuses reachable in a single step from this one
+    pub fn next_statuses(&self) -> &'static [TransactionStatus] {
+        use TransactionStatus::*;
+        match self {
+            Pending => &[Authorize, Capture, Settlement, Deny, Cancel, Expire, Failure],
+            Authorize => &[Capture, Deny, Cancel, Expire],
+            Capture => &[Settlement, Deny, Cancel, Refund, PartialRefund, Chargeback, PartialChargeback],
+            Settlement => &[Refund, PartialRefund, Chargeback, PartialChargeback],
+            PartialRefund => &[PartialRefund, Refund, Chargeback, PartialChargeback],
+            PartialChargeback => &[PartialChargeback, Chargeback, Refund, PartialRefund],
+            Deny | Cancel | Expire | Failure | Refund | Chargeback | Unknown => &[]
+        }
+    }
+
+    /// Whether `next` is a legal single step from this status
+    pub fn can_transition_to(&self, next: TransactionStatus) -> bool {
+        self.next_statuses().contains(&next)
+    }
+
+    /// Whether `next` can be reached from this status in any number of steps
+    pub fn can_reach(&self, next: TransactionStatus) -> bool {
+        let mut visited = vec![*self];
+        let mut queue = vec![*self];
+        while let Some(status) = queue.pop() {
+            for &candidate in status.next_statuses() {
+                if candidate == next {
+                    return true
+                }
+                if !visited.contains(&candidate) {
+                    visited.push(candidate);
+                    queue.push(candidate);
+                }
+            }
+        }
+        false
+    }
+
+    /// No further status change can happen
+    /// (`deny`, `cancel`, `expire`, `failure`, `refund`, `chargeback`)
+    ///
+    /// `Unknown` is not final, since nothing is known about it.
+    pub fn is_final(&self) -> bool {
+        *self != TransactionStatus::Unknown && self.next_statuses().is_empty()
+    }
+}
+
+/// Outcome of comparing a new status against the previously recorded one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// Legal step forward in the lifecycle, or the first recorded state
+    Valid,
+    /// Same state as previously recorded, e.g. a retried notification
+    Duplicate,
+    /// Older state arriving after a newer one, safe to ignore
+    OutOfOrder,
+    /// Not a documented transition
+    Invalid,
+    /// One of the statuses is not known by this crate
+    Unknown
+}
+
+/// Transaction status together with its fraud status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransactionState {
+    pub status: TransactionStatus,
+    pub fraud_status: Option<FraudStatus>
+}
+
+impl TransactionState {
+    pub fn new(status: TransactionStatus, fraud_status: Option<FraudStatus>) -> Self {
+        Self { status, fraud_status }
+    }
+
+    /// State of a `Transactions::status_typed` response,
+    /// `None` when the response has no `transaction_status`
+    pub fn from_response(response: &TransactionStatusResponse) -> Option<Self> {
+        response.transaction_status.map(|status| Self::new(status, response.fraud_status))
+    }
+
+    /// State of a transaction notification,
+    /// `None` for subscription, pay account and unknown notifications
+    pub fn from_notification(notification: &Notification) -> Option<Self> {
+        notification.transaction().map(Self::from)
+    }
+
+    /// See [`TransactionStatus::is_final`]
+    pub fn is_final(&self) -> bool {
+        self.status.is_final()
+    }
+
+    /// Customer has paid: `settlement`, `capture` not challenged by fraud detection,
+    /// or a partially refunded / charged back payment
+    pub fn is_paid(&self) -> bool {
+        use TransactionStatus::*;
+        match self.status {
+            Settlement | PartialRefund | PartialChargeback => true,
+            Capture => matches!(self.fraud_status, None | Some(FraudStatus::Accept)),
+            _ => false
+        }
+    }
+
+    /// Classify the move from this state to `next`
+    pub fn transition_to(&self, next: &TransactionState) -> Transition {
+        use TransactionStatus::*;
+        if self.status == Unknown || next.status == Unknown {
+            return Transition::Unknown
+        }
+        if self == next {
+            return Transition::Duplicate
+        }
+        if self.status == next.status {
+            // a challenged capture is accepted or denied through `approve` / `deny`
+            return match (self.status, self.fraud_status, next.fraud_status) {
+                (Capture, Some(FraudStatus::Challenge), Some(FraudStatus::Accept)) => Transition::Valid,
+                (Capture, Some(FraudStatus::Accept), Some(FraudStatus::Challenge)) => Transition::OutOfOrder,
+                (PartialRefund | PartialChargeback, _, _) => Transition::Valid,
+                _ => Transition::Invalid
+            }
+        }
+        if self.status.can_transition_to(next.status) {
+            Transition::Valid
+        } else if next.status.can_reach(self.status) {
+            Transition::OutOfOrder
+        } else {
+            Transition::Invalid
+        }
+    }
+}
+
+impl From<&TransactionNotification> for TransactionState {
+    fn from(notification: &TransactionNotification) -> Self {
+        Self::new(notification.transaction_status, notification.fraud_status)
+    }
+}
+
+/// Classify a new state against the previously recorded one, if any
+pub fn check_transition(previous: Option<&TransactionState>, next: &TransactionState) -> Transition {
+    match previous {
+        Some(previous) => previous.transition_to(next),
+        None if next.status == TransactionStatus::Unknown => Transition::Unknown,
+        None => Transition::Valid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use TransactionStatus::*;
+
+    fn state(status: TransactionStatus) -> TransactionState {
+        TransactionState::new(status, None)
+    }
+
+    #[test]
+    fn final_and_paid() {
+        for status in [Deny, Cancel, Expire, Failure, Refund, Chargeback] {
+            assert!(status.is_final(), "{:?}", status);
+            assert!(!state(status).is_paid(), "{:?}", status);
+        }
+        for status in [Pending, Authorize, Capture, Settlement, PartialRefund, PartialChargeback, Unknown] {
+            assert!(!status.is_final(), "{:?}", status);
+        }
+        assert!(state(Settlement).is_paid());
+        assert!(TransactionState::new(Capture, Some(FraudStatus::Accept)).is_paid());
+        assert!(!TransactionState::new(Capture, Some(FraudStatus::Challenge)).is_paid());
+        assert!(!state(Pending).is_paid());
+    }
+
+    #[test]
+    fn transitions() {
+        assert_eq!(state(Pending).transition_to(&state(Settlement)), Transition::Valid);
+        assert_eq!(state(Settlement).transition_to(&state(Refund)), Transition::Valid);
+        assert_eq!(state(Settlement).transition_to(&state(Settlement)), Transition::Duplicate);
+        assert_eq!(state(Settlement).transition_to(&state(Pending)), Transition::OutOfOrder);
+        assert_eq!(state(Refund).transition_to(&state(Capture)), Transition::OutOfOrder);
+        assert_eq!(state(Expire).transition_to(&state(Settlement)), Transition::Invalid);
+        assert_eq!(state(Cancel).transition_to(&state(Expire)), Transition::Invalid);
+        assert_eq!(state(Pending).transition_to(&state(Unknown)), Transition::Unknown);
+    }
+
+    #[test]
+    fn challenged_capture() {
+        let challenge = TransactionState::new(Capture, Some(FraudStatus::Challenge));
+        let accept = TransactionState::new(Capture, Some(FraudStatus::Accept));
+        assert_eq!(challenge.transition_to(&accept), Transition::Valid);
+        assert_eq!(accept.transition_to(&challenge), Transition::OutOfOrder);
+        assert_eq!(challenge.transition_to(&state(Deny)), Transition::Valid);
+    }
+
+    #[test]
+    fn first_state() {
+        assert_eq!(check_transition(None, &state(Pending)), Transition::Valid);
+        assert_eq!(check_transition(Some(&state(Pending)), &state(Expire)), Transition::Valid);
+    }
+
+    #[test]
+    fn from_notification() {
+        let notification: Notification = serde_json::json!({
+            "transaction_id": "883af6a4-c1b4-4d39-9bd8-b148fcebe853",
+            "order_id": "1466323342",
+            "payment_type": "gopay",
+            "transaction_status": "settlement"
+        }).to_string().parse().unwrap();
+        let state = TransactionState::from_notification(&notification).unwrap();
+        assert!(state.is_paid());
+    }
+}
@@ -0,0 +1,50 @@
+//! Typed Request and Response Models
+//!
+//! Serde models that can be used instead of building raw JSON parameters
+//! and reading `HashMap<String, Value>` responses.
+
+use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::MidtransError;
+
+pub mod charge;
+pub mod response;
+pub mod notification;
+pub mod lifecycle;
+pub mod payment_link;
+pub mod invoice;
+
+#[doc(inline)]
+pub use charge::{ChargeRequest, PaymentMethod};
+#[doc(inline)]
+pub use notification::Notification;
+#[doc(inline)]
+pub use payment_link::{PaymentLinkRequest, PaymentLinkResponse};
+#[doc(inline)]
+pub use invoice::{InvoiceRequest, InvoiceResponse};
+#[doc(inline)]
+pub use lifecycle::{TransactionState, Transition, check_transition};
+#[doc(inline)]
+pub use response::{
+    TransactionStatus,
+    FraudStatus,
+    ChargeResponse,
+    TransactionStatusResponse,
+    TransactionStatusB2bResponse,
+    RefundResponse,
+    SnapTransaction,
+    SubscriptionResponse,
+    SubscriptionUpdateResponse,
+    PayAccountResponse,
+    CardRegisterResponse,
+    CardTokenResponse,
+    PointInquiryResponse,
+    CardBinResponse
+};
+
+/// Convert decoded `HashMap` response into a typed response model
+pub fn from_response<T: DeserializeOwned>(response: HashMap<String, Value>) -> Result<T, MidtransError> {
+    let response = Value::Object(response.into_iter().collect());
+    Ok(serde_json::from_value(response)?)
+}
@@ -0,0 +1,338 @@
+//! HTTP Notification Models
+//!
+//! Midtrans sends a different notification body for each payment type, and for
+//! subscriptions and linked pay accounts. [`Notification`] decodes the body into
+//! the matching variant.
+//! (refer to: <https://docs.midtrans.com/docs/https-notification-webhooks>)
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::MidtransError;
+use super::from_response as decode;
+use super::response::{TransactionStatus, FraudStatus, VaNumber, Refund, SubscriptionResponse};
+
+/// Typed HTTP notification
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    /// `credit_card`
+    CreditCard(CardNotification),
+    /// `bank_transfer` with `va_numbers` (BCA, BNI, BRI, CIMB)
+    BankTransfer(BankTransferNotification),
+    /// `bank_transfer` with `permata_va_number`
+    Permata(PermataNotification),
+    /// `echannel` (Mandiri Bill Payment)
+    Echannel(EchannelNotification),
+    /// `gopay`, `shopeepay` and `qris`
+    EWallet(EWalletNotification),
+    /// `cstore` (Indomaret, Alfamart)
+    Cstore(CstoreNotification),
+    /// Any other payment type, e.g. `akulaku` or `kredivo`
+    Other(TransactionNotification),
+    /// Subscription status change
+    Subscription(SubscriptionResponse),
+    /// Linked pay account (e.g. GoPay tokenization) status change
+    PayAccount(PayAccountNotification),
+    /// Notification body that doesn't match any known shape
+    Unknown(HashMap<String, Value>)
+}
+
+/// Fields shared by every transaction notification
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TransactionNotification {
+    pub transaction_id: String,
+    pub order_id: String,
+    pub transaction_status: TransactionStatus,
+    pub status_code: Option<String>,
+    pub status_message: Option<String>,
+    pub merchant_id: Option<String>,
+    pub gross_amount: Option<String>,
+    pub currency: Option<String>,
+    pub payment_type: Option<String>,
+    pub transaction_time: Option<String>,
+    pub settlement_time: Option<String>,
+    pub expiry_time: Option<String>,
+    pub fraud_status: Option<FraudStatus>,
+    pub signature_key: Option<String>,
+    pub refund_amount: Option<String>,
+    #[serde(default)]
+    pub refunds: Vec<Refund>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CardNotification {
+    pub approval_code: Option<String>,
+    pub masked_card: Option<String>,
+    pub bank: Option<String>,
+    pub card_type: Option<String>,
+    pub eci: Option<String>,
+    pub channel_response_code: Option<String>,
+    pub channel_response_message: Option<String>,
+    pub saved_token_id: Option<String>,
+    pub saved_token_id_expired_at: Option<String>,
+    #[serde(flatten)]
+    pub transaction: TransactionNotification
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BankTransferNotification {
+    #[serde(default)]
+    pub va_numbers: Vec<VaNumber>,
+    #[serde(flatten)]
+    pub transaction: TransactionNotification
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PermataNotification {
+    pub permata_va_number: String,
+    #[serde(flatten)]
+    pub transaction: TransactionNotification
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EchannelNotification {
+    pub bill_key: String,
+    pub biller_code: String,
+    #[serde(flatten)]
+    pub transaction: TransactionNotification
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EWalletNotification {
+    pub acquirer: Option<String>,
+    pub issuer: Option<String>,
+    #[serde(flatten)]
+    pub transaction: TransactionNotification
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CstoreNotification {
+    pub payment_code: String,
+    pub store: Option<String>,
+    #[serde(flatten)]
+    pub transaction: TransactionNotification
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PayAccountNotification {
+    pub account_id: String,
+    pub account_status: String,
+    pub payment_type: Option<String>,
+    pub status_code: Option<String>,
+    pub signature_key: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+impl Notification {
+    /// Decode a notification body into the matching variant
+    pub fn from_json(notification: HashMap<String, Value>) -> Result<Self, MidtransError> {
+        let has = |field: &str| notification.contains_key(field);
+        let payment_type = notification.get("payment_type").and_then(Value::as_str);
+
+        if has("transaction_id") {
+            let notification = match payment_type {
+                Some("credit_card") => Notification::CreditCard(decode(notification)?),
+                Some("bank_transfer") if has("permata_va_number") => Notification::Permata(decode(notification)?),
+                Some("bank_transfer") => Notification::BankTransfer(decode(notification)?),
+                Some("echannel") => Notification::Echannel(decode(notification)?),
+                Some("gopay" | "shopeepay" | "qris") => Notification::EWallet(decode(notification)?),
+                Some("cstore") => Notification::Cstore(decode(notification)?),
+                _ => Notification::Other(decode(notification)?)
+            };
+            return Ok(notification)
+        }
+
+        if has("account_id") && has("account_status") {
+            return Ok(Notification::PayAccount(decode(notification)?))
+        }
+
+        if has("schedule") {
+            return Ok(Notification::Subscription(decode(notification)?))
+        }
+
+        Ok(Notification::Unknown(notification))
+    }
+
+    /// Fields shared by every transaction notification,
+    /// `None` for subscription, pay account and unknown notifications
+    pub fn transaction(&self) -> Option<&TransactionNotification> {
+        match self {
+            Notification::CreditCard(n) => Some(&n.transaction),
+            Notification::BankTransfer(n) => Some(&n.transaction),
+            Notification::Permata(n) => Some(&n.transaction),
+            Notification::Echannel(n) => Some(&n.transaction),
+            Notification::EWallet(n) => Some(&n.transaction),
+            Notification::Cstore(n) => Some(&n.transaction),
+            Notification::Other(n) => Some(n),
+            _ => None
+        }
+    }
+}
+
+impl FromStr for Notification {
+    type Err = MidtransError;
+
+    fn from_str(notification: &str) -> Result<Self, Self::Err> {
+        let notification: HashMap<String, Value> = serde_json::from_str(notification)?;
+        Notification::from_json(notification)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(value: Value) -> Notification {
+        value.to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn credit_card() {
+        let notification = parse(json!({
+            "transaction_time": "2020-01-09 18:27:19",
+            "transaction_status": "capture",
+            "transaction_id": "57d5293c-e65f-4a29-95e4-5959c3fa335b",
+            "status_message": "midtrans payment notification",
+            "status_code": "200",
+            "signature_key": "16d6f84b2fb0468e2a9cf99a8ac4e5d803d42180347aaa70cb2a7abb13b5c6130458ca9c71956a962c0827637cd3bc7d40b21a8ae9fab12c7c3efe351b18d00a",
+            "payment_type": "credit_card",
+            "order_id": "Postman-1578568851",
+            "merchant_id": "G141532850",
+            "masked_card": "481111-1114",
+            "gross_amount": "10000.00",
+            "fraud_status": "accept",
+            "eci": "05",
+            "currency": "IDR",
+            "channel_response_message": "Approved",
+            "channel_response_code": "00",
+            "card_type": "credit",
+            "bank": "bni",
+            "approval_code": "1578569243927"
+        }));
+        match notification {
+            Notification::CreditCard(card) => {
+                assert_eq!(card.approval_code.as_deref(), Some("1578569243927"));
+                assert_eq!(card.masked_card.as_deref(), Some("481111-1114"));
+                assert_eq!(card.transaction.transaction_status, TransactionStatus::Capture);
+                assert_eq!(card.transaction.fraud_status, Some(FraudStatus::Accept));
+                assert!(card.transaction.extra.is_empty());
+            },
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn bank_transfer_and_permata() {
+        let notification = parse(json!({
+            "va_numbers": [{"va_number": "812785002530231", "bank": "bca"}],
+            "transaction_status": "settlement",
+            "transaction_id": "9aed5972-5b6a-401e-894b-a32c91ed1a3a",
+            "order_id": "1466323342",
+            "payment_type": "bank_transfer",
+            "gross_amount": "20000.00",
+            "payment_amounts": [{"paid_at": "2016-06-19 15:02:22", "amount": "20000.00"}]
+        }));
+        match notification {
+            Notification::BankTransfer(n) => {
+                assert_eq!(n.va_numbers[0].va_number, "812785002530231");
+                assert!(n.transaction.extra.contains_key("payment_amounts"));
+            },
+            other => panic!("unexpected {:?}", other)
+        }
+
+        let notification = parse(json!({
+            "permata_va_number": "8562000087926752",
+            "transaction_status": "pending",
+            "transaction_id": "883af6a4-c1b4-4d39-9bd8-b148fcebe853",
+            "order_id": "1466323342",
+            "payment_type": "bank_transfer"
+        }));
+        assert!(matches!(notification, Notification::Permata(n) if n.permata_va_number == "8562000087926752"));
+    }
+
+    #[test]
+    fn echannel_ewallet_cstore() {
+        let base = json!({
+            "transaction_status": "pending",
+            "transaction_id": "883af6a4-c1b4-4d39-9bd8-b148fcebe853",
+            "order_id": "1466323342"
+        });
+        let with = |extra: Value| {
+            let mut value = base.clone();
+            value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            parse(value)
+        };
+
+        let echannel = with(json!({"payment_type": "echannel", "bill_key": "990000000260", "biller_code": "70012"}));
+        assert!(matches!(echannel, Notification::Echannel(n) if n.bill_key == "990000000260"));
+
+        let gopay = with(json!({"payment_type": "gopay"}));
+        assert!(matches!(gopay, Notification::EWallet(n) if n.transaction.payment_type.as_deref() == Some("gopay")));
+
+        let qris = with(json!({"payment_type": "qris", "acquirer": "gopay", "issuer": "gopay"}));
+        assert!(matches!(qris, Notification::EWallet(n) if n.acquirer.as_deref() == Some("gopay")));
+
+        let cstore = with(json!({"payment_type": "cstore", "payment_code": "25709650945026", "store": "indomaret"}));
+        assert!(matches!(cstore, Notification::Cstore(n) if n.payment_code == "25709650945026"));
+
+        let akulaku = with(json!({"payment_type": "akulaku"}));
+        assert!(matches!(akulaku, Notification::Other(_)));
+    }
+
+    #[test]
+    fn refund() {
+        let notification = parse(json!({
+            "transaction_status": "partial_refund",
+            "transaction_id": "883af6a4-c1b4-4d39-9bd8-b148fcebe853",
+            "order_id": "1466323342",
+            "payment_type": "credit_card",
+            "refund_amount": "5000.00",
+            "refunds": [{
+                "refund_chargeback_id": 120120,
+                "refund_amount": "5000.00",
+                "created_at": "2020-01-09 18:30:00",
+                "reason": "customer request",
+                "refund_key": "reference1"
+            }]
+        }));
+        let transaction = notification.transaction().unwrap();
+        assert_eq!(transaction.transaction_status, TransactionStatus::PartialRefund);
+        assert_eq!(transaction.refunds[0].refund_key.as_deref(), Some("reference1"));
+    }
+
+    #[test]
+    fn subscription_pay_account_unknown() {
+        let subscription = parse(json!({
+            "id": "d98a63b8-97e4-4059-825f-0f62340407e9",
+            "name": "MONTHLY_2019",
+            "amount": "14000",
+            "currency": "IDR",
+            "status": "active",
+            "payment_type": "credit_card",
+            "schedule": {"interval": 1, "interval_unit": "month"}
+        }));
+        assert!(matches!(subscription, Notification::Subscription(_)));
+        assert!(subscription.transaction().is_none());
+
+        let pay_account = parse(json!({
+            "account_id": "f0ad3f4b-9ae4-4d6a-8a54-ed8c5ec06d5c",
+            "account_status": "ENABLED",
+            "payment_type": "gopay"
+        }));
+        assert!(matches!(pay_account, Notification::PayAccount(n) if n.account_status == "ENABLED"));
+
+        let unknown = parse(json!({"foo": "bar"}));
+        assert!(matches!(unknown, Notification::Unknown(n) if n["foo"] == "bar"));
+    }
+
+    #[test]
+    fn invalid_transaction_notification() {
+        let result: Result<Notification, _> = json!({"transaction_id": "abc"}).to_string().parse();
+        assert!(matches!(result, Err(MidtransError::JsonDecodeError(_))));
+    }
+}
@@ -0,0 +1,147 @@
+//! Payment Link Models
+//!
+//! (more params detail refer to: <https://docs.midtrans.com/reference/payment-link-api>)
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::charge::{CustomerDetails, ItemDetails};
+
+/// Typed parameters of `/v1/payment-links` API call, used by `CoreApi::create_payment_link_typed`
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentLinkRequest {
+    pub transaction_details: PaymentLinkTransactionDetails,
+    /// Ask the customer to fill their details before paying
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_required: Option<bool>,
+    /// How many times the link can be paid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage_limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<PaymentLinkExpiry>,
+    /// Payment channels shown to the customer, e.g. `credit_card`, `bca_va`, `gopay`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_payments: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_details: Option<Vec<ItemDetails>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customer_details: Option<CustomerDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callbacks: Option<PaymentLinkCallbacks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit_card: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_field1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_field2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_field3: Option<String>
+}
+
+impl PaymentLinkRequest {
+    pub fn new(order_id: String, gross_amount: i64) -> Self {
+        Self {
+            transaction_details: PaymentLinkTransactionDetails {
+                order_id,
+                gross_amount,
+                payment_link_id: None
+            },
+            customer_required: None,
+            usage_limit: None,
+            expiry: None,
+            enabled_payments: None,
+            item_details: None,
+            customer_details: None,
+            title: None,
+            callbacks: None,
+            credit_card: None,
+            custom_field1: None,
+            custom_field2: None,
+            custom_field3: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PaymentLinkTransactionDetails {
+    pub order_id: String,
+    pub gross_amount: i64,
+    /// Custom id used in the payment link url
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_link_id: Option<String>
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentLinkExpiry {
+    /// Format `yyyy-MM-dd HH:mm Z`, e.g. `2024-04-01 18:00 +0700`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    pub duration: u32,
+    pub unit: PaymentLinkExpiryUnit
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentLinkExpiryUnit {
+    Minutes,
+    Hours,
+    Days
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PaymentLinkCallbacks {
+    pub finish: String
+}
+
+/// Response of `CoreApi::create_payment_link` and `CoreApi::get_payment_link`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PaymentLinkResponse {
+    pub order_id: Option<String>,
+    pub payment_url: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn request() {
+        let mut request = PaymentLinkRequest::new("order-1".to_string(), 190000);
+        request.transaction_details.payment_link_id = Some("for-payment-123".to_string());
+        request.customer_required = Some(false);
+        request.usage_limit = Some(1);
+        request.expiry = Some(PaymentLinkExpiry {
+            start_time: Some("2024-04-01 18:00 +0700".to_string()),
+            duration: 20,
+            unit: PaymentLinkExpiryUnit::Days
+        });
+        request.enabled_payments = Some(vec!["credit_card".to_string(), "bca_va".to_string()]);
+        assert_eq!(serde_json::to_value(request).unwrap(), json!({
+            "transaction_details": {
+                "order_id": "order-1",
+                "gross_amount": 190000,
+                "payment_link_id": "for-payment-123"
+            },
+            "customer_required": false,
+            "usage_limit": 1,
+            "expiry": {"start_time": "2024-04-01 18:00 +0700", "duration": 20, "unit": "days"},
+            "enabled_payments": ["credit_card", "bca_va"]
+        }));
+    }
+
+    #[test]
+    fn response() {
+        let response: PaymentLinkResponse = serde_json::from_value(json!({
+            "order_id": "order-1",
+            "payment_url": "https://app.sandbox.midtrans.com/payment-links/for-payment-123",
+            "status_code": "201"
+        })).unwrap();
+        assert_eq!(response.order_id.as_deref(), Some("order-1"));
+        assert_eq!(response.extra["status_code"], "201");
+    }
+}
@@ -0,0 +1,441 @@
+//! Response Models
+//!
+//! Every response keeps the fields it doesn't know about in `extra`, so nothing
+//! returned by Midtrans is lost.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `transaction_status` of Midtrans transaction
+/// (refer to: <https://docs.midtrans.com/docs/https-notification-webhooks#transaction-status>)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    Pending,
+    Authorize,
+    Capture,
+    Settlement,
+    Deny,
+    Cancel,
+    Expire,
+    Failure,
+    Refund,
+    PartialRefund,
+    Chargeback,
+    PartialChargeback,
+    #[serde(other)]
+    Unknown
+}
+
+/// `fraud_status` of Midtrans transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FraudStatus {
+    Accept,
+    Challenge,
+    Deny,
+    #[serde(other)]
+    Unknown
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VaNumber {
+    pub bank: String,
+    pub va_number: String
+}
+
+/// Deeplink / QR code actions of e-wallet and QRIS payments
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Action {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub fields: Vec<String>
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Refund {
+    pub refund_chargeback_id: Option<i64>,
+    pub refund_amount: Option<String>,
+    pub created_at: Option<String>,
+    pub reason: Option<String>,
+    pub refund_key: Option<String>,
+    pub refund_method: Option<String>,
+    pub bank_confirmed_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::charge` and `CoreApi::capture`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChargeResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    pub transaction_id: Option<String>,
+    pub order_id: Option<String>,
+    pub merchant_id: Option<String>,
+    pub gross_amount: Option<String>,
+    pub currency: Option<String>,
+    pub payment_type: Option<String>,
+    pub transaction_time: Option<String>,
+    pub transaction_status: Option<TransactionStatus>,
+    pub fraud_status: Option<FraudStatus>,
+    pub expiry_time: Option<String>,
+    #[serde(default)]
+    pub va_numbers: Vec<VaNumber>,
+    pub permata_va_number: Option<String>,
+    pub bill_key: Option<String>,
+    pub biller_code: Option<String>,
+    pub payment_code: Option<String>,
+    pub store: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    pub redirect_url: Option<String>,
+    pub qr_string: Option<String>,
+    pub masked_card: Option<String>,
+    pub bank: Option<String>,
+    pub card_type: Option<String>,
+    pub approval_code: Option<String>,
+    pub saved_token_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `Transactions::status` and transaction actions
+/// (`approve`, `deny`, `cancel`, `expire`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TransactionStatusResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    pub transaction_id: Option<String>,
+    pub order_id: Option<String>,
+    pub merchant_id: Option<String>,
+    pub gross_amount: Option<String>,
+    pub currency: Option<String>,
+    pub payment_type: Option<String>,
+    pub transaction_time: Option<String>,
+    pub settlement_time: Option<String>,
+    pub transaction_status: Option<TransactionStatus>,
+    pub fraud_status: Option<FraudStatus>,
+    pub signature_key: Option<String>,
+    #[serde(default)]
+    pub va_numbers: Vec<VaNumber>,
+    pub permata_va_number: Option<String>,
+    pub bill_key: Option<String>,
+    pub biller_code: Option<String>,
+    pub payment_code: Option<String>,
+    pub masked_card: Option<String>,
+    pub bank: Option<String>,
+    pub card_type: Option<String>,
+    pub approval_code: Option<String>,
+    pub refund_amount: Option<String>,
+    #[serde(default)]
+    pub refunds: Vec<Refund>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `Transactions::statusb2b`, every transaction of a B2B order
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TransactionStatusB2bResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    #[serde(default)]
+    pub transactions: Vec<TransactionStatusResponse>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `Transactions::refund` and `Transactions::refund_direct`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RefundResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    pub transaction_id: Option<String>,
+    pub order_id: Option<String>,
+    pub gross_amount: Option<String>,
+    pub currency: Option<String>,
+    pub payment_type: Option<String>,
+    pub transaction_time: Option<String>,
+    pub transaction_status: Option<TransactionStatus>,
+    pub refund_chargeback_id: Option<i64>,
+    pub refund_amount: Option<String>,
+    pub refund_key: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `Snap::create_transaction`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SnapTransaction {
+    pub token: String,
+    pub redirect_url: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SubscriptionSchedule {
+    pub interval: u32,
+    pub interval_unit: String,
+    pub max_interval: Option<u32>,
+    pub current_interval: Option<u32>,
+    pub start_time: Option<String>,
+    pub previous_execution_at: Option<String>,
+    pub next_execution_at: Option<String>
+}
+
+/// Response of `CoreApi::create_subscription` and `CoreApi::get_subscription`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SubscriptionResponse {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub amount: Option<String>,
+    pub currency: Option<String>,
+    pub created_at: Option<String>,
+    pub schedule: Option<SubscriptionSchedule>,
+    pub status: Option<String>,
+    pub token: Option<String>,
+    pub payment_type: Option<String>,
+    #[serde(default)]
+    pub transaction_ids: Vec<String>,
+    pub metadata: Option<Value>,
+    pub customer_details: Option<Value>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::update_subscription`, `CoreApi::enable_subscription`
+/// and `CoreApi::disable_subscription`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SubscriptionUpdateResponse {
+    pub status_message: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::link_payment_account`, `CoreApi::get_payment_account`
+/// and `CoreApi::unlink_payment_account`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PayAccountResponse {
+    pub status_code: String,
+    pub payment_type: Option<String>,
+    pub account_id: Option<String>,
+    pub account_status: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    pub metadata: Option<Value>,
+    pub channel_response_code: Option<String>,
+    pub channel_response_message: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::card_register`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CardRegisterResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    pub saved_token_id: Option<String>,
+    pub transaction_id: Option<String>,
+    pub masked_card: Option<String>,
+    #[serde(default)]
+    pub validation_messages: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::card_token`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CardTokenResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    pub token_id: Option<String>,
+    pub bank: Option<String>,
+    pub hash: Option<String>,
+    /// 3DS authentication url, when the card requires it
+    pub redirect_url: Option<String>,
+    #[serde(default)]
+    pub validation_messages: Vec<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::card_point_inquiry`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PointInquiryResponse {
+    pub status_code: String,
+    pub status_message: Option<String>,
+    pub point_balance_amount: Option<String>,
+    pub transaction_time: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Card BIN details, in `data` of [`CardBinResponse`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CardBin {
+    pub bin: Option<String>,
+    /// e.g. `CREDIT` or `DEBIT`
+    pub bin_type: Option<String>,
+    pub bin_class: Option<String>,
+    /// e.g. `VISA` or `MASTERCARD`
+    pub brand: Option<String>,
+    pub bank: Option<String>,
+    pub bank_code: Option<String>,
+    pub country_name: Option<String>,
+    pub country_code: Option<String>,
+    pub channel: Option<String>,
+    pub registration_required: Option<bool>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+/// Response of `CoreApi::card_bin`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CardBinResponse {
+    pub data: CardBin,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::from_response;
+    use serde_json::json;
+
+    fn hashmap(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn charge_response_bank_transfer() {
+        let response: ChargeResponse = from_response(hashmap(json!({
+            "status_code": "201",
+            "status_message": "Success, Bank Transfer transaction is created",
+            "transaction_id": "73ebe57c-ffb0-42a6-93fa-c5022a7f316e",
+            "order_id": "test-transaction-321",
+            "merchant_id": "G738628276",
+            "gross_amount": "24145.00",
+            "currency": "IDR",
+            "payment_type": "bank_transfer",
+            "transaction_time": "2022-11-18 21:01:24",
+            "transaction_status": "pending",
+            "fraud_status": "accept",
+            "va_numbers": [{"bank": "bca", "va_number": "28276362079"}],
+            "some_new_field": "kept"
+        }))).unwrap();
+        assert_eq!(response.transaction_status, Some(TransactionStatus::Pending));
+        assert_eq!(response.fraud_status, Some(FraudStatus::Accept));
+        assert_eq!(response.va_numbers[0].va_number, "28276362079");
+        assert_eq!(response.extra["some_new_field"], "kept");
+    }
+
+    #[test]
+    fn transaction_status_unknown_value() {
+        let response: TransactionStatusResponse = from_response(hashmap(json!({
+            "status_code": "200",
+            "transaction_status": "something_new",
+            "refunds": [{"refund_chargeback_id": 1, "refund_amount": "5000.00"}]
+        }))).unwrap();
+        assert_eq!(response.transaction_status, Some(TransactionStatus::Unknown));
+        assert_eq!(response.refunds[0].refund_chargeback_id, Some(1));
+    }
+
+    #[test]
+    fn snap_transaction() {
+        let response: SnapTransaction = from_response(hashmap(json!({
+            "status_code": "201",
+            "token": "1115ee78-9e17-4089-9992-a6f39e355fa7",
+            "redirect_url": "https://app.sandbox.midtrans.com/snap/v3/redirection/1115ee78"
+        }))).unwrap();
+        assert_eq!(response.token, "1115ee78-9e17-4089-9992-a6f39e355fa7");
+        assert_eq!(response.extra["status_code"], "201");
+    }
+
+    #[test]
+    fn subscription_response() {
+        let response: SubscriptionResponse = from_response(hashmap(json!({
+            "id": "b6eb6a04-33e6-46a2-a298-cd78e55b3a3f",
+            "name": "SUBS-RUST",
+            "amount": "100000",
+            "currency": "IDR",
+            "status": "active",
+            "schedule": {"interval": 1, "interval_unit": "day", "max_interval": 7},
+            "status_code": "200"
+        }))).unwrap();
+        assert_eq!(response.schedule.unwrap().interval_unit, "day");
+    }
+
+    #[test]
+    fn card_bin_response() {
+        let response: CardBinResponse = from_response(hashmap(json!({
+            "data": {
+                "registration_required": null,
+                "country_name": "INDONESIA",
+                "country_code": "ID",
+                "channel": "online_offline",
+                "brand": "VISA",
+                "bin_type": "CREDIT",
+                "bin_class": "GOLD",
+                "bin": "455633",
+                "bank_code": "BNI",
+                "bank": "BANK NEGARA INDONESIA"
+            },
+            "status_code": "200"
+        }))).unwrap();
+        assert_eq!(response.data.bank_code.as_deref(), Some("BNI"));
+        assert_eq!(response.data.brand.as_deref(), Some("VISA"));
+        assert_eq!(response.data.registration_required, None);
+    }
+
+    #[test]
+    fn transaction_status_b2b_response() {
+        let response: TransactionStatusB2bResponse = from_response(hashmap(json!({
+            "status_code": "200",
+            "status_message": "Success, transactions is found",
+            "transactions": [
+                {"status_code": "200", "order_id": "order-1", "transaction_status": "settlement"},
+                {"status_code": "200", "order_id": "order-1", "transaction_status": "pending"}
+            ]
+        }))).unwrap();
+        assert_eq!(response.transactions.len(), 2);
+        assert_eq!(response.transactions[0].transaction_status, Some(TransactionStatus::Settlement));
+    }
+
+    #[test]
+    fn card_token_response() {
+        let response: CardTokenResponse = from_response(hashmap(json!({
+            "status_code": "200",
+            "status_message": "Credit card token is created as Token ID.",
+            "token_id": "481111-1114-d3b5b7e0-1fa4-4a6d-8ee7-0f5c3c8d2a3f",
+            "bank": "bni",
+            "hash": "481111-1114-mami"
+        }))).unwrap();
+        assert_eq!(response.bank.as_deref(), Some("bni"));
+        assert!(response.validation_messages.is_empty());
+
+        let response: CardRegisterResponse = from_response(hashmap(json!({
+            "status_code": "400",
+            "validation_messages": ["card_exp_month is required"]
+        }))).unwrap();
+        assert_eq!(response.validation_messages, ["card_exp_month is required"]);
+    }
+
+    #[test]
+    fn subscription_update_response() {
+        let response: SubscriptionUpdateResponse = from_response(hashmap(json!({
+            "status_message": "Subscription is updated."
+        }))).unwrap();
+        assert_eq!(response.status_message.as_deref(), Some("Subscription is updated."));
+    }
+
+    #[test]
+    fn missing_required_field() {
+        let response: Result<SnapTransaction, _> = from_response(hashmap(json!({"status_code": "201"})));
+        assert!(matches!(response, Err(crate::MidtransError::JsonDecodeError(_))));
+    }
+}
@@ -0,0 +1,141 @@
+//! Request Parameters
+//!
+//! [`Parameters`] is what the clients accept as the JSON object sent to Midtrans:
+//! JSON text as `&str` / `String`, a `serde_json::Value`, a typed request model
+//! such as [`crate::models::ChargeRequest`], or any other `Serialize` value wrapped in [`Json`].
+
+use std::collections::HashMap;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::MidtransError;
+use crate::models::{ChargeRequest, InvoiceRequest, PaymentLinkRequest};
+
+/// Value that makes the JSON object of a request
+pub trait Parameters {
+    /// JSON object of the request, `Value::Null` when there are no parameters.
+    /// Any other JSON value returns `MidtransError::InvalidParameters`.
+    fn to_parameters(&self) -> Result<Value, MidtransError>;
+}
+
+/// Typed parameters, any `Serialize` value that makes a JSON object
+///
+/// ```
+/// use midtransclient::parameters::{Json, Parameters};
+///
+/// #[derive(serde::Serialize)]
+/// struct Charge { payment_type: String }
+///
+/// let parameters = Json(Charge { payment_type: "gopay".to_string() });
+/// assert_eq!(parameters.to_parameters().unwrap()["payment_type"], "gopay");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> Parameters for Json<T> {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        object(serde_json::to_value(&self.0)?)
+    }
+}
+
+/// JSON text, an empty string means no parameters
+impl Parameters for str {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        match self {
+            "" => Ok(Value::Null),
+            text => object(serde_json::from_str(text)?)
+        }
+    }
+}
+
+/// JSON text, an empty string means no parameters
+impl Parameters for String {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        self.as_str().to_parameters()
+    }
+}
+
+impl Parameters for Value {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        object(self.clone())
+    }
+}
+
+impl Parameters for Map<String, Value> {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        Ok(Value::Object(self.clone()))
+    }
+}
+
+impl Parameters for HashMap<String, Value> {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        Ok(Value::Object(self.clone().into_iter().collect()))
+    }
+}
+
+impl<T: Parameters + ?Sized> Parameters for &T {
+    fn to_parameters(&self) -> Result<Value, MidtransError> {
+        (**self).to_parameters()
+    }
+}
+
+macro_rules! serialize_parameters {
+    ($($model:ty),*) => {
+        $(
+            impl Parameters for $model {
+                fn to_parameters(&self) -> Result<Value, MidtransError> {
+                    Json(self).to_parameters()
+                }
+            }
+        )*
+    };
+}
+
+serialize_parameters!(ChargeRequest, PaymentLinkRequest, InvoiceRequest);
+
+fn object(parameters: Value) -> Result<Value, MidtransError> {
+    match parameters {
+        Value::Object(_) | Value::Null => Ok(parameters),
+        parameters => Err(MidtransError::InvalidParameters(
+            format!("expected a JSON object, got {}", parameters)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Charge {
+        payment_type: String
+    }
+
+    #[derive(Serialize)]
+    struct OrderId(String);
+
+    #[test]
+    fn string_is_decoded_once() -> Result<(), MidtransError> {
+        let expected = json!({"payment_type": "gopay"});
+        assert_eq!(r#"{"payment_type": "gopay"}"#.to_parameters()?, expected);
+        assert_eq!(r#"{"payment_type": "gopay"}"#.to_string().to_parameters()?, expected);
+        assert_eq!(expected.to_parameters()?, expected);
+        assert_eq!(Json(Charge { payment_type: "gopay".to_string() }).to_parameters()?, expected);
+        assert_eq!("".to_parameters()?, Value::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn only_json_text_is_decoded() {
+        // a typed value serialized to a string is not JSON text
+        let parameters = Json(OrderId(r#"{"payment_type": "gopay"}"#.to_string())).to_parameters();
+        assert!(matches!(parameters, Err(MidtransError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn non_object_rejected() {
+        assert!(matches!("[1, 2]".to_parameters(), Err(MidtransError::InvalidParameters(_))));
+        assert!(matches!(json!("gopay").to_parameters(), Err(MidtransError::InvalidParameters(_))));
+        assert!(matches!(Json(10000).to_parameters(), Err(MidtransError::InvalidParameters(_))));
+    }
+}
@@ -0,0 +1,297 @@
+//! Per-request Options
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use crate::MidtransError;
+
+pub(crate) const IDEMPOTENCY_KEY: &str = "idempotency-key";
+const OVERRIDE_NOTIFICATION: &str = "x-override-notification";
+const APPEND_NOTIFICATION: &str = "x-append-notification";
+const MAX_NOTIFICATION_URLS: usize = 3;
+
+/// Function used to generate `Idempotency-Key` when none is given explicitly
+pub type IdempotencyKeyGenerator = fn() -> String;
+
+/// Default [`IdempotencyKeyGenerator`] that returns random UUID v4
+pub fn generate_idempotency_key() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Options that only apply to a single request, unlike `ApiConfig::custom_headers`
+/// which is shared by every request of the instance.
+///
+/// Accepted by `CoreApi::charge_with_options`, `CoreApi::capture_with_options`,
+/// `Snap::create_transaction_with_options`, `Transactions::refund_with_options`
+/// and `Transactions::refund_direct_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub idempotency_key: Option<String>,
+    pub override_notification: Vec<String>,
+    pub append_notification: Vec<String>,
+    pub headers: HeaderMap
+}
+
+impl RequestOptions {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> RequestOptionsBuilder {
+        RequestOptionsBuilder {
+            idempotency_key: None,
+            override_notification: Vec::new(),
+            append_notification: Vec::new(),
+            headers: HeaderMap::new()
+        }
+    }
+
+    /// Merge config level `custom_headers` with the headers of this request.
+    ///
+    /// `Idempotency-Key` is taken from `idempotency_key`, or from `generator`
+    /// when it is not set. Notification urls are validated, see [`validate_notification_urls`].
+    pub fn merge_headers(
+        &self,
+        custom_headers: &Option<HeaderMap>,
+        generator: Option<IdempotencyKeyGenerator>
+    ) -> Result<Option<HeaderMap>, MidtransError> {
+        let mut headers = custom_headers.clone().unwrap_or_default();
+
+        for (key, val) in self.headers.iter() {
+            headers.insert(key, val.clone());
+        }
+
+        let idempotency_key = match (&self.idempotency_key, generator) {
+            (Some(key), _) => Some(key.clone()),
+            (None, Some(generator)) => Some(generator()),
+            (None, None) => None
+        };
+
+        if let Some(key) = idempotency_key {
+            let value = HeaderValue::from_str(&key).map_err(|_| {
+                MidtransError::InvalidRequestOptions(format!("Invalid Idempotency-Key: {:?}", key))
+            })?;
+            headers.insert(IDEMPOTENCY_KEY, value);
+        }
+
+        for (name, urls) in [
+            (OVERRIDE_NOTIFICATION, &self.override_notification),
+            (APPEND_NOTIFICATION, &self.append_notification)
+        ] {
+            if urls.is_empty() {
+                continue;
+            }
+            validate_notification_urls(urls)?;
+            let value = HeaderValue::from_str(&urls.join(",")).map_err(|_| {
+                MidtransError::InvalidRequestOptions(format!("Invalid notification url: {:?}", urls))
+            })?;
+            headers.insert(name, value);
+        }
+
+        match headers.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(headers))
+        }
+    }
+}
+
+/// Check notification urls as required by Midtrans:
+/// at most 3 urls and every url must be a valid `https` url.
+pub fn validate_notification_urls(urls: &[String]) -> Result<(), MidtransError> {
+    if urls.len() > MAX_NOTIFICATION_URLS {
+        return Err(MidtransError::InvalidRequestOptions(format!(
+            "At most {} notification urls are allowed, got {}",
+            MAX_NOTIFICATION_URLS,
+            urls.len()
+        )));
+    }
+
+    for url in urls {
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if parsed.scheme() == "https" && !url.contains(',') => (),
+            _ => return Err(MidtransError::InvalidRequestOptions(format!(
+                "Notification url must be a valid https url, got {:?}",
+                url
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+pub struct RequestOptionsBuilder {
+    idempotency_key: Option<String>,
+    override_notification: Vec<String>,
+    append_notification: Vec<String>,
+    headers: HeaderMap
+}
+
+impl RequestOptionsBuilder {
+    /// `Idempotency-Key` header, reuse the same key when retrying the same charge/refund
+    pub fn idempotency_key(&mut self, idempotency_key: String) -> &mut Self {
+        self.idempotency_key = Some(idempotency_key);
+        self
+    }
+
+    /// `X-Override-Notification`, replace the notification urls set on dashboard
+    /// for this transaction (max 3 https urls)
+    pub fn override_notification(&mut self, urls: Vec<String>) -> &mut Self {
+        self.override_notification = urls;
+        self
+    }
+
+    /// `X-Append-Notification`, add notification urls on top of the ones set on dashboard
+    /// for this transaction (max 3 https urls)
+    pub fn append_notification(&mut self, urls: Vec<String>) -> &mut Self {
+        self.append_notification = urls;
+        self
+    }
+
+    /// Extra header sent only with this request
+    pub fn header(&mut self, key: HeaderName, value: HeaderValue) -> &mut Self {
+        self.headers.insert(key, value);
+        self
+    }
+
+    pub fn headers(&mut self, headers: HeaderMap) -> &mut Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn build(&self) -> RequestOptions {
+        RequestOptions {
+            idempotency_key: self.idempotency_key.clone(),
+            override_notification: self.override_notification.clone(),
+            append_notification: self.append_notification.clone(),
+            headers: self.headers.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CoreApi, Snap, Transactions};
+    use crate::transport::FakeTransport;
+
+    #[test]
+    fn merge_headers_empty() -> Result<(), MidtransError> {
+        let options = RequestOptions::default();
+        assert!(options.merge_headers(&None, None)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn merge_headers_with_custom_headers() -> Result<(), MidtransError> {
+        let mut custom_headers = HeaderMap::new();
+        custom_headers.insert("X-Custom-Header", "config".parse().unwrap());
+        custom_headers.insert("X-Other-Header", "config".parse().unwrap());
+        let options = RequestOptions::new()
+            .idempotency_key("order-1-charge".to_string())
+            .header(HeaderName::from_static("x-custom-header"), HeaderValue::from_static("request"))
+            .build();
+        let headers = options.merge_headers(&Some(custom_headers), None)?.unwrap();
+        assert_eq!(headers["x-custom-header"], "request");
+        assert_eq!(headers["x-other-header"], "config");
+        assert_eq!(headers["idempotency-key"], "order-1-charge");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_headers_with_generator() -> Result<(), MidtransError> {
+        let options = RequestOptions::default();
+        let headers = options.merge_headers(&None, Some(generate_idempotency_key))?.unwrap();
+        assert_eq!(headers["idempotency-key"].len(), 36);
+
+        let options = RequestOptions::new().idempotency_key("explicit".to_string()).build();
+        let headers = options.merge_headers(&None, Some(generate_idempotency_key))?.unwrap();
+        assert_eq!(headers["idempotency-key"], "explicit");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_headers_invalid_idempotency_key() {
+        let options = RequestOptions::new().idempotency_key("bad\nkey".to_string()).build();
+        let headers = options.merge_headers(&None, None);
+        assert!(matches!(headers, Err(MidtransError::InvalidRequestOptions(_))));
+    }
+
+    #[test]
+    fn merge_headers_with_notification_urls() -> Result<(), MidtransError> {
+        let options = RequestOptions::new()
+            .override_notification(vec![
+                "https://store-a.example.com/notify".to_string(),
+                "https://backup.example.com/notify".to_string()
+            ])
+            .append_notification(vec!["https://audit.example.com/notify".to_string()])
+            .build();
+        let headers = options.merge_headers(&None, None)?.unwrap();
+        assert_eq!(
+            headers["x-override-notification"],
+            "https://store-a.example.com/notify,https://backup.example.com/notify"
+        );
+        assert_eq!(headers["x-append-notification"], "https://audit.example.com/notify");
+        Ok(())
+    }
+
+    #[test]
+    fn notification_urls_max_three() {
+        let urls: Vec<String> = (0..4).map(|i| format!("https://example.com/{}", i)).collect();
+        let options = RequestOptions::new().override_notification(urls).build();
+        assert!(matches!(
+            options.merge_headers(&None, None),
+            Err(MidtransError::InvalidRequestOptions(_))
+        ));
+    }
+
+    #[test]
+    fn notification_urls_https_only() {
+        for url in ["http://example.com/notify", "not a url", "https://a.com/x,https://b.com/y"] {
+            let options = RequestOptions::new().append_notification(vec![url.to_string()]).build();
+            assert!(matches!(
+                options.merge_headers(&None, None),
+                Err(MidtransError::InvalidRequestOptions(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn charge_with_idempotency_key() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "201"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        let options = RequestOptions::new()
+            .idempotency_key("order-1-charge".to_string())
+            .build();
+        core.charge_with_options(r#"{"payment_type": "gopay"}"#, &options)?;
+        assert_eq!(transport.last_request().headers["idempotency-key"], "order-1-charge");
+
+        core.charge(r#"{"payment_type": "gopay"}"#)?;
+        assert!(!transport.last_request().headers.contains_key("idempotency-key"));
+        Ok(())
+    }
+
+    #[test]
+    fn refund_with_generated_idempotency_key() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .idempotency_key_generator(|| "generated-key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        core.refund("order-1".to_string(), r#"{"amount": 5000}"#)?;
+        assert_eq!(transport.last_request().headers["idempotency-key"], "generated-key");
+        Ok(())
+    }
+
+    #[test]
+    fn create_transaction_with_notification_urls() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(201, r#"{"token": "abc", "redirect_url": "https://app.sandbox.midtrans.com"}"#);
+        let snap = Snap::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        let options = RequestOptions::new()
+            .override_notification(vec!["https://store-a.example.com/notify".to_string()])
+            .build();
+        snap.create_transaction_with_options(r#"{"transaction_details": {}}"#, &options)?;
+        let request = transport.last_request();
+        assert_eq!(request.headers["x-override-notification"], "https://store-a.example.com/notify");
+        assert!(snap.api_config.get_custom_headers().is_none());
+        Ok(())
+    }
+}
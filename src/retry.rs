@@ -0,0 +1,290 @@
+//! Retry Policy
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use reqwest::Method;
+use crate::{MidtransError, transport::TransportRequest, request_options::IDEMPOTENCY_KEY};
+
+/// Retry policy with exponential backoff, used for transient failures.
+///
+/// Only safe requests are retried: `GET` requests (e.g. `Transactions::status`,
+/// `CoreApi::get_subscription`) and requests that carry an `Idempotency-Key` header
+/// (e.g. `CoreApi::charge` with idempotency key).
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: f64,
+    pub retry_status_codes: Vec<u16>,
+    pub retry_on_request_error: bool
+}
+
+impl RetryPolicy {
+    /// `max_attempts` includes the first attempt, so `1` means no retry
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(max_attempts: u32) -> RetryPolicyBuilder {
+        RetryPolicyBuilder {
+            max_attempts,
+            base_delay: None,
+            max_delay: None,
+            jitter: None,
+            retry_status_codes: None,
+            retry_on_request_error: None
+        }
+    }
+
+    /// Return true if `request` is safe to be sent more than once
+    pub fn is_retryable_request(&self, request: &TransportRequest) -> bool {
+        request.method == Method::GET || request.headers.contains_key(IDEMPOTENCY_KEY)
+    }
+
+    /// Return true if `error` is considered a transient failure
+    pub fn is_retryable_error(&self, error: &MidtransError) -> bool {
+        match error {
+            MidtransError::ApiError(e) => self.retry_status_codes.contains(&e.status_code),
+            MidtransError::RequestError(_) |
+            MidtransError::TransportError(_) |
+            MidtransError::TimeoutError(_) => self.retry_on_request_error,
+            _ => false
+        }
+    }
+
+    /// Return true if `request` that failed with `error` on `attempt` (starting from 1)
+    /// should be sent again
+    pub fn should_retry(&self, request: &TransportRequest, attempt: u32, error: &MidtransError) -> bool {
+        attempt < self.max_attempts
+            && self.is_retryable_request(request)
+            && self.is_retryable_error(error)
+    }
+
+    /// Delay before the next attempt, `base_delay * 2^(attempt - 1)` capped at `max_delay`
+    /// and reduced by a random factor up to `jitter`
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self.base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        delay.mul_f64(1.0 - jitter)
+    }
+}
+
+/// Attempts of a single request under an optional [`RetryPolicy`], shared by the
+/// blocking and async clients so that only the sleep differs between them
+pub(crate) struct Retry<'a> {
+    policy: Option<&'a RetryPolicy>,
+    attempt: u32
+}
+
+impl<'a> Retry<'a> {
+    pub(crate) fn new(policy: Option<&'a RetryPolicy>) -> Self {
+        Retry { policy, attempt: 1 }
+    }
+
+    /// Delay to wait before sending `request` again after it failed with `error`,
+    /// `None` when it must not be retried
+    pub(crate) fn next_delay(&mut self, request: &TransportRequest, error: &MidtransError) -> Option<Duration> {
+        let policy = self.policy.filter(|policy| policy.should_retry(request, self.attempt, error))?;
+        let delay = policy.delay(self.attempt);
+        self.attempt += 1;
+        Some(delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(3).build()
+    }
+}
+
+/// Cheap source of randomness for jitter, good enough to spread retries
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos % 1_000) / 1_000.0
+}
+
+pub struct RetryPolicyBuilder {
+    max_attempts: u32,
+    base_delay: Option<Duration>,
+    max_delay: Option<Duration>,
+    jitter: Option<f64>,
+    retry_status_codes: Option<Vec<u16>>,
+    retry_on_request_error: Option<bool>
+}
+
+impl RetryPolicyBuilder {
+    pub fn base_delay(&mut self, base_delay: Duration) -> &mut Self {
+        self.base_delay = Some(base_delay);
+        self
+    }
+
+    pub fn max_delay(&mut self, max_delay: Duration) -> &mut Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Fraction (`0.0` - `1.0`) of the delay that may be randomly removed
+    pub fn jitter(&mut self, jitter: f64) -> &mut Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
+    pub fn retry_status_codes(&mut self, retry_status_codes: Vec<u16>) -> &mut Self {
+        self.retry_status_codes = Some(retry_status_codes);
+        self
+    }
+
+    /// Retry on connection level errors (connection reset, DNS, TLS, ...)
+    pub fn retry_on_request_error(&mut self, value: bool) -> &mut Self {
+        self.retry_on_request_error = Some(value);
+        self
+    }
+
+    pub fn build(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts.max(1),
+            base_delay: self.base_delay.unwrap_or(Duration::from_millis(200)),
+            max_delay: self.max_delay.unwrap_or(Duration::from_secs(5)),
+            jitter: self.jitter.unwrap_or(0.5),
+            retry_status_codes: self.retry_status_codes.clone()
+                .unwrap_or_else(|| vec![500, 502, 503, 504]),
+            retry_on_request_error: self.retry_on_request_error.unwrap_or(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use reqwest::header::HeaderMap;
+    use crate::{CoreApi, Transactions, Transport, error::ApiError, transport::TransportResponse};
+
+    struct FlakyTransport {
+        failures_left: Mutex<u32>,
+        attempts: Mutex<u32>
+    }
+
+    impl Transport for FlakyTransport {
+        fn send(&self, _request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+            *self.attempts.lock().unwrap() += 1;
+            let mut failures_left = self.failures_left.lock().unwrap();
+            let body = match *failures_left {
+                0 => r#"{"status_code": "200", "transaction_status": "settlement"}"#,
+                _ => r#"{"status_code": "503", "status_message": "Service Unavailable"}"#
+            };
+            *failures_left = failures_left.saturating_sub(1);
+            Ok(TransportResponse { status_code: 200, headers: HeaderMap::new(), body: body.to_string() })
+        }
+    }
+
+    fn flaky_core(failures: u32) -> (CoreApi, Arc<FlakyTransport>) {
+        let transport = Arc::new(FlakyTransport {
+            failures_left: Mutex::new(failures),
+            attempts: Mutex::new(0)
+        });
+        let policy = RetryPolicy::new(3).base_delay(Duration::from_millis(1)).build();
+        let core = CoreApi::new(false, "server_key".to_string())
+            .retry_policy(policy)
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        (core, transport)
+    }
+
+    fn request(method: Method) -> TransportRequest {
+        TransportRequest {
+            method,
+            url: "https://api.sandbox.midtrans.com/v2/charge".to_string(),
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: None
+        }
+    }
+
+    fn api_error(status_code: u16) -> MidtransError {
+        MidtransError::ApiError(ApiError::new(status_code, HashMap::new(), String::new()))
+    }
+
+    #[test]
+    fn build_default() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.retry_status_codes, vec![500, 502, 503, 504]);
+        assert!(policy.retry_on_request_error);
+    }
+
+    #[test]
+    fn only_safe_requests_are_retried() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(&request(Method::GET), 1, &api_error(503)));
+        assert!(!policy.should_retry(&request(Method::POST), 1, &api_error(503)));
+
+        let mut post = request(Method::POST);
+        post.headers.insert("Idempotency-Key", "key-1".parse().unwrap());
+        assert!(policy.should_retry(&post, 1, &api_error(503)));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let policy = RetryPolicy::default();
+        assert!(!policy.should_retry(&request(Method::GET), 1, &api_error(404)));
+        assert!(policy.should_retry(&request(Method::GET), 1, &api_error(502)));
+        assert!(!policy.should_retry(&request(Method::GET), 3, &api_error(502)));
+    }
+
+    #[test]
+    fn delay_is_exponential_and_capped() {
+        let policy = RetryPolicy::new(10)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(500))
+            .jitter(0.0)
+            .build();
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn delay_with_jitter_is_not_longer() {
+        let policy = RetryPolicy::new(3)
+            .base_delay(Duration::from_millis(100))
+            .jitter(1.0)
+            .build();
+        assert!(policy.delay(1) <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn retry_stops_after_max_attempts() {
+        let policy = RetryPolicy::new(3).jitter(0.0).build();
+        let mut retry = Retry::new(Some(&policy));
+        assert_eq!(retry.next_delay(&request(Method::GET), &api_error(503)), Some(Duration::from_millis(200)));
+        assert_eq!(retry.next_delay(&request(Method::GET), &api_error(503)), Some(Duration::from_millis(400)));
+        assert_eq!(retry.next_delay(&request(Method::GET), &api_error(503)), None);
+
+        let mut retry = Retry::new(None);
+        assert_eq!(retry.next_delay(&request(Method::GET), &api_error(503)), None);
+    }
+
+    #[test]
+    fn status_is_retried_until_success() -> Result<(), MidtransError> {
+        let (core, transport) = flaky_core(2);
+        let response = core.status("order-id".to_string())?;
+        assert_eq!(response["transaction_status"], "settlement");
+        assert_eq!(*transport.attempts.lock().unwrap(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn charge_without_idempotency_key_is_not_retried() {
+        let (core, transport) = flaky_core(1);
+        let response = core.charge(r#"{"payment_type": "gopay"}"#);
+        assert!(matches!(response, Err(MidtransError::ApiError(ref e)) if e.status_code == 503));
+        assert_eq!(*transport.attempts.lock().unwrap(), 1);
+    }
+}
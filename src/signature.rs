@@ -0,0 +1,114 @@
+//! Notification Signature
+//!
+//! Every HTTP notification from Midtrans carries a `signature_key`, which is
+//! `SHA512(order_id + status_code + gross_amount + server_key)` as lowercase hex.
+//! (refer to: <https://docs.midtrans.com/docs/https-notification-webhooks#verifying-notification-authenticity>)
+
+use std::collections::HashMap;
+use serde_json::Value;
+use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
+use crate::MidtransError;
+
+/// Compute the expected `signature_key` of a notification
+pub fn signature_key(order_id: &str, status_code: &str, gross_amount: &str, server_key: &str) -> String {
+    let mut hasher = Sha512::new();
+    hasher.update(order_id.as_bytes());
+    hasher.update(status_code.as_bytes());
+    hasher.update(gross_amount.as_bytes());
+    hasher.update(server_key.as_bytes());
+    hasher.finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Check `signature_key` against the one computed from the other fields,
+/// comparing in constant time
+pub fn verify_signature_key(
+    order_id: &str,
+    status_code: &str,
+    gross_amount: &str,
+    server_key: &str,
+    received: &str
+) -> Result<(), MidtransError> {
+    let expected = signature_key(order_id, status_code, gross_amount, server_key);
+    let valid: bool = expected.as_bytes().ct_eq(received.to_ascii_lowercase().as_bytes()).into();
+    match valid {
+        true => Ok(()),
+        false => Err(MidtransError::InvalidSignature)
+    }
+}
+
+/// Verify the `signature_key` of a decoded notification body
+///
+/// Missing or non-string fields are treated as an invalid signature.
+pub fn verify_notification(notification: &HashMap<String, Value>, server_key: &str) -> Result<(), MidtransError> {
+    let field = |name: &str| notification.get(name)
+        .and_then(Value::as_str)
+        .ok_or(MidtransError::InvalidSignature);
+
+    verify_signature_key(
+        field("order_id")?,
+        field("status_code")?,
+        field("gross_amount")?,
+        server_key,
+        field("signature_key")?
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    const SERVER_KEY: &str = "SB-Mid-server-TEST";
+
+    fn notification(signature_key: &str) -> HashMap<String, Value> {
+        serde_json::from_value(json!({
+            "order_id": "order-123",
+            "status_code": "200",
+            "gross_amount": "10000.00",
+            "transaction_status": "settlement",
+            "signature_key": signature_key
+        })).unwrap()
+    }
+
+    #[test]
+    fn signature_key_is_sha512_hex() {
+        let key = signature_key("order-123", "200", "10000.00", SERVER_KEY);
+        assert_eq!(key.len(), 128);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    }
+
+    #[test]
+    fn known_signature_key() {
+        // printf '%s' "123420010000.00abc" | sha512sum
+        let key = signature_key("1234", "200", "10000.00", "abc");
+        assert_eq!(
+            key,
+            "37c8448245f8fafc96bcbafecfdec6511fdf701651adf7781035328c3d8296a664e089b6a8eeaa85e1e6f019cf7517fba6ba5561e4456860fc3aa7e818343eed"
+        );
+    }
+
+    #[test]
+    fn verify_valid_notification() {
+        let key = signature_key("order-123", "200", "10000.00", SERVER_KEY);
+        assert!(verify_notification(&notification(&key), SERVER_KEY).is_ok());
+    }
+
+    #[test]
+    fn verify_forged_notification() {
+        let key = signature_key("order-123", "200", "10000.00", "another-key");
+        let result = verify_notification(&notification(&key), SERVER_KEY);
+        assert!(matches!(result, Err(MidtransError::InvalidSignature)));
+    }
+
+    #[test]
+    fn verify_missing_signature() {
+        let mut notification = notification("");
+        notification.remove("signature_key");
+        let result = verify_notification(&notification, SERVER_KEY);
+        assert!(matches!(result, Err(MidtransError::InvalidSignature)));
+    }
+}
@@ -1,18 +1,38 @@
 //! Snap
 
 use std::collections::HashMap;
-use reqwest::{Method, header::HeaderMap, Proxy};
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{header::HeaderMap, Proxy};
+use crate::parameters::Parameters;
 use serde_json::Value;
-use crate::{ApiConfig, MidtransError, http_client::MidtransClient, Transactions};
+use crate::{ApiConfig, MidtransError, RequestOptions, RetryPolicy, http_client::{MidtransClient, to_parameters}, Transactions, Transport};
+use crate::transport::ReqwestTransport;
+use crate::config::{ApiConfigBuilder, Environment, Operation};
+use crate::endpoint::Endpoint;
+use crate::models::{from_response, SnapTransaction};
 
 type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
 
 /// Snap struct used to do request to Midtrans Snap API
 pub struct Snap {
     pub api_config: ApiConfig,
+    transport: Arc<dyn Transport>
 }
 
-impl MidtransClient for Snap {}
+impl MidtransClient for Snap {
+    fn get_transport(&self) -> Option<&dyn Transport> {
+        Some(self.transport.as_ref())
+    }
+
+    fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.api_config.get_retry_policy().as_ref()
+    }
+
+    fn get_timeout(&self, operation: Operation) -> Option<Duration> {
+        self.api_config.get_timeout(operation)
+    }
+}
 
 impl Transactions for Snap {
     /// Getter for ApiConfig
@@ -27,98 +47,141 @@ impl Transactions for Snap {
 }
 
 pub struct SnapBuilder {
-    is_production: bool,
-    server_key: String,
-    client_key: Option<String>,
-    custom_headers: Option<HeaderMap>,
-    proxies: Option<Proxy>
+    api_config: ApiConfigBuilder,
+    transport: Option<Arc<dyn Transport>>
 }
 
 impl SnapBuilder {
+    /// Override the environment chosen by `is_production`, e.g. `Environment::Custom`
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.api_config.environment(environment);
+        self
+    }
+
     pub fn client_key(&mut self, client_key: String) -> &mut Self {
-        self.client_key = Some(client_key);
+        self.api_config.client_key(client_key);
         self
     }
 
     pub fn custom_headers(&mut self, custom_headers: HeaderMap) -> &mut Self {
-        self.custom_headers = Some(custom_headers);
+        self.api_config.custom_header(custom_headers);
         self
     }
 
     pub fn proxies(&mut self, proxies: Proxy) -> &mut Self {
-        self.proxies = Some(proxies);
+        self.api_config.proxies(proxies);
         self
     }
 
-    pub fn build(&self) -> Result<Snap, MidtransError> {
-        let mut api_config = ApiConfig::new(self.is_production, self.server_key.clone());
+    /// Retry transient failures of safe requests, see [`RetryPolicy`]
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.api_config.retry_policy(retry_policy);
+        self
+    }
 
-        if let Some(key) = &self.client_key {
-            api_config.client_key(key.clone());
-        }
+    /// Timeout for establishing the connection to Midtrans
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.connect_timeout(timeout);
+        self
+    }
 
-        if let Some(headers) = &self.custom_headers {
-            api_config.custom_header(headers.clone());
-        }
+    /// Timeout of the whole request, exceeding it returns `MidtransError::TimeoutError`
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.api_config.timeout(timeout);
+        self
+    }
 
-        if let Some(proxy) = &self.proxies {
-            api_config.proxies(proxy.clone());
-        }
+    /// Override `timeout` for specific [`Operation`]
+    pub fn operation_timeout(&mut self, operation: Operation, timeout: Duration) -> &mut Self {
+        self.api_config.operation_timeout(operation, timeout);
+        self
+    }
 
-        let api_config = api_config.build();
+    /// Use custom [`Transport`] instead of the default `reqwest` one
+    pub fn transport(&mut self, transport: Arc<dyn Transport>) -> &mut Self {
+        self.transport = Some(transport);
+        self
+    }
 
-        Ok(Snap { api_config })
+    /// Use preconfigured `reqwest::blocking::Client` for every request of this instance.
+    pub fn http_client(&mut self, http_client: reqwest::blocking::Client) -> &mut Self {
+        self.transport = Some(Arc::new(ReqwestTransport::from(http_client)));
+        self
+    }
+
+    pub fn build(&self) -> Result<Snap, MidtransError> {
+        let api_config = self.api_config.build();
+
+        let transport: Arc<dyn Transport> = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::from_config(&api_config)?)
+        };
+
+        Ok(Snap { api_config, transport })
     }
 }
 
 impl Snap {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(is_production: bool, server_key: String) -> SnapBuilder {
         SnapBuilder {
-            is_production,
-            server_key,
-            client_key: None,
-            custom_headers: None,
-            proxies: None
+            api_config: ApiConfig::new(is_production, server_key),
+            transport: None
         }
     }
 
+    /// Setter for custom [`Transport`]
+    pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+        self.transport = transport;
+    }
+
     /// Trigger API call to Snap API
     ///
     /// ### Argument
     ///
-    /// `parameters` is a `&str` of Core API JSON
+    /// `parameters` is any [`Parameters`] JSON object, e.g. `serde_json::json!`, a typed model or a `&str` of Core API JSON
     /// (more params detail refer to: <https://snap-docs.midtrans.com>)
     ///
     /// ### Return
     ///
     /// HashMap from JSON decoded response, that contains `token` and `redirect_url`
     ///
-    pub fn create_transaction(&self, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/snap/v1/transactions",
-            self.api_config.get_snap_base_url()
-        );
+    pub fn create_transaction<P: Parameters + ?Sized>(&self, parameters: &P) -> MidtransResult {
+        self.create_transaction_with_options(parameters, &RequestOptions::default())
+    }
+
+    /// Same as [`Self::create_transaction`] with per-request [`RequestOptions`],
+    /// e.g. `X-Override-Notification` / `X-Append-Notification` urls
+    pub fn create_transaction_with_options<P: Parameters + ?Sized>(&self, parameters: &P, options: &RequestOptions) -> MidtransResult {
+        let custom_headers = options.merge_headers(
+            self.api_config.get_custom_headers(),
+            None
+        )?;
 
         let response = self.request(
-            Method::POST,
+            Endpoint::snap_transaction(&self.api_config),
             self.api_config.get_server_key(),
-            &api_url,
-            parameters,
-            self.api_config.get_custom_headers().clone(),
+            &to_parameters(parameters)?,
+            custom_headers,
             self.api_config.get_proxies().clone()
         )?;
 
         Ok(response)
     }
 
+    /// `create_transaction` returning a typed [`SnapTransaction`]
+    pub fn create_transaction_typed<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<SnapTransaction, MidtransError> {
+        from_response(self.create_transaction(parameters)?)
+    }
+
     /// Wrapper method that call `create_transaction` and directly return `token`
-    pub fn create_transaction_token(&self, parameters: &str) -> Result<Value, MidtransError> {
+    pub fn create_transaction_token<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<Value, MidtransError> {
         let response = self.create_transaction(parameters)?;
         Ok(response["token"].clone())
     }
 
     /// Wrapper method that call `create_transaction` and directly return `redirect_url`
-    pub fn create_transaction_redirect_url(&self, parameters: &str) -> Result<Value, MidtransError> {
+    pub fn create_transaction_redirect_url<P: Parameters + ?Sized>(&self, parameters: &P) -> Result<Value, MidtransError> {
         let response = self.create_transaction(parameters)?;
         Ok(response["redirect_url"].clone())
     }
@@ -148,26 +211,44 @@ mod test {
 
     mod helper {
         use super::*;
+        #[cfg(not(feature = "mock-server"))]
         use std::env;
         use chrono;
 
+        #[cfg(not(feature = "mock-server"))]
         pub(crate) fn server_key() -> String {
             env::var("MIDTRANS_SERVER_KEY").expect("SERVER_KEY NOT FOUND")
         }
 
+        #[cfg(not(feature = "mock-server"))]
         pub(crate) fn client_key() -> String {
             env::var("MIDTRANS_CLIENT_KEY").expect("CLIENT_KEY NOT FOUND")
         }
 
+        #[cfg(feature = "mock-server")]
+        pub(crate) fn server_key() -> String {
+            crate::mock_server::TEST_SERVER_KEY.to_string()
+        }
+
+        #[cfg(feature = "mock-server")]
+        pub(crate) fn client_key() -> String {
+            crate::mock_server::TEST_CLIENT_KEY.to_string()
+        }
+
+        /// Sandbox instance, or one backed by the shared mock with the `mock-server` feature
         pub(crate) fn generate_snap_api_instance() -> Snap {
-            Snap::new(false, server_key())
-                .client_key(client_key())
-                .build()
-                .unwrap()
+            let mut snap = Snap::new(false, server_key());
+            snap.client_key(client_key());
+            #[cfg(feature = "mock-server")]
+            snap.transport(crate::mock_server::shared());
+            snap.build().unwrap()
         }
 
         pub(crate) fn generate_order_id(test_number: u8) -> String {
             let now = chrono::offset::Local::now().format("%Y%m%d%H%M%S").to_string();
+            // tests share one mock, keep their order ids apart within the same second
+            #[cfg(feature = "mock-server")]
+            let now = format!("{}-{}", now, &uuid::Uuid::new_v4().simple().to_string()[..8]);
             format!("rust-midtransclient-test{}-{}", test_number, now)
         }
 
@@ -285,11 +366,12 @@ mod test {
     mod snap {
         use super::*;
         use super::helper::*;
+        use crate::transport::FakeTransport;
 
         #[test]
         fn new() {
             let snap = Snap::new(false, "server_key".to_string()).build().unwrap();
-            assert_eq!(snap.api_config.get_is_production(), false);
+            assert!(!snap.api_config.get_is_production());
             assert_eq!(snap.api_config.get_server_key(), "server_key");
             assert_eq!(snap.api_config.get_client_key(), "");
             assert!(snap.api_config.get_custom_headers().is_none());
@@ -310,7 +392,7 @@ mod test {
                 .proxies(proxies)
                 .build()
                 .unwrap();
-            assert_eq!(snap.api_config.get_is_production(), false);
+            assert!(!snap.api_config.get_is_production());
             assert_eq!(snap.api_config.get_server_key(), "server_key");
             assert_eq!(snap.api_config.get_client_key(), "client_key");
             assert_eq!(snap.api_config.get_custom_headers().clone().unwrap(), custom_headers);
@@ -345,7 +427,7 @@ mod test {
             let order_id = generate_order_id(1);
             let parameters = generate_param_min(&order_id);
             let token = snap.create_transaction_token(&parameters)?;
-            assert!(token.to_string().len() > 0);
+            assert!(!token.to_string().is_empty());
             Ok(())
         }
 
@@ -355,7 +437,7 @@ mod test {
             let order_id = generate_order_id(1);
             let parameters = generate_param_min(&order_id);
             let redirect_url = snap.create_transaction_redirect_url(&parameters)?;
-            assert!(redirect_url.to_string().len() > 0);
+            assert!(!redirect_url.to_string().is_empty());
             Ok(())
         }
 
@@ -446,5 +528,17 @@ mod test {
             assert!(transaction.contains_key("redirect_url"));
             Ok(())
         }
+
+        #[test]
+        fn create_transaction_without_generated_idempotency_key() -> Result<(), MidtransError> {
+            let transport = FakeTransport::new(201, r#"{"token": "abc", "redirect_url": "https://app.sandbox.midtrans.com"}"#);
+            let mut snap = Snap::new(false, "server_key".to_string())
+                .transport(transport.clone())
+                .build()?;
+            snap.api_config.set_idempotency_key_generator(|| "generated-key".to_string());
+            snap.create_transaction(r#"{"transaction_details": {}}"#)?;
+            assert!(!transport.last_request().headers.contains_key("idempotency-key"));
+            Ok(())
+        }
     }
 }
\ No newline at end of file
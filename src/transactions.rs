@@ -1,9 +1,12 @@
 //! Transactions
 
 use std::collections::HashMap;
-use reqwest::Method;
+use crate::parameters::Parameters;
 use serde_json::Value;
-use crate::{MidtransError, ApiConfig, http_client::MidtransClient};
+use crate::signature;
+use crate::endpoint::{Endpoint, is_path_segment};
+use crate::models::{from_response, TransactionStatusResponse, TransactionStatusB2bResponse, RefundResponse};
+use crate::{MidtransError, ApiConfig, RequestOptions, http_client::{MidtransClient, to_parameters}};
 
 type MidtransResult = Result<HashMap<String, Value>, MidtransError>;
 
@@ -13,17 +16,10 @@ pub trait Transactions: MidtransClient {
     fn set_api_config(&mut self, api_config: ApiConfig);
 
     fn status(&self, transaction_id: String) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/status",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
-
         let response = self.request(
-            Method::GET,
+            Endpoint::status(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.get_api_config().get_custom_headers().clone(),
             self.get_api_config().get_proxies().clone()
         )?;
@@ -32,17 +28,10 @@ pub trait Transactions: MidtransClient {
     }
 
     fn statusb2b(&self, transaction_id: String) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/status/b2b",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
-
         let response = self.request(
-            Method::GET,
+            Endpoint::status_b2b(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.get_api_config().get_custom_headers().clone(),
             self.get_api_config().get_proxies().clone()
         )?;
@@ -51,17 +40,10 @@ pub trait Transactions: MidtransClient {
     }
 
     fn approve(&self, transaction_id: String) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/approve",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
-
         let response = self.request(
-            Method::POST,
+            Endpoint::approve(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.get_api_config().get_custom_headers().clone(),
             self.get_api_config().get_proxies().clone()
         )?;
@@ -70,17 +52,10 @@ pub trait Transactions: MidtransClient {
     }
 
     fn deny(&self, transaction_id: String) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/deny",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
-
         let response = self.request(
-            Method::POST,
+            Endpoint::deny(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.get_api_config().get_custom_headers().clone(),
             self.get_api_config().get_proxies().clone()
         )?;
@@ -89,17 +64,10 @@ pub trait Transactions: MidtransClient {
     }
 
     fn cancel(&self, transaction_id: String) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/cancel",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
-
         let response = self.request(
-            Method::POST,
+            Endpoint::cancel(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.get_api_config().get_custom_headers().clone(),
             self.get_api_config().get_proxies().clone()
         )?;
@@ -108,17 +76,10 @@ pub trait Transactions: MidtransClient {
     }
 
     fn expire(&self, transaction_id: String) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/expire",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
-
         let response = self.request(
-            Method::POST,
+            Endpoint::expire(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            "",
+            &Value::Null,
             self.get_api_config().get_custom_headers().clone(),
             self.get_api_config().get_proxies().clone()
         )?;
@@ -126,51 +87,213 @@ pub trait Transactions: MidtransClient {
         Ok(response)
     }
 
-    fn refund(&self, transaction_id: String, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/refund",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
+    fn refund<P: Parameters + ?Sized>(&self, transaction_id: String, parameters: &P) -> MidtransResult where Self: Sized {
+        self.refund_with_options(transaction_id, parameters, &RequestOptions::default())
+    }
+
+    /// `refund` with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    fn refund_with_options<P: Parameters + ?Sized>(&self, transaction_id: String, parameters: &P, options: &RequestOptions) -> MidtransResult where Self: Sized {
+        let custom_headers = options.merge_headers(
+            self.get_api_config().get_custom_headers(),
+            self.get_api_config().get_idempotency_key_generator()
+        )?;
 
         let response = self.request(
-            Method::POST,
+            Endpoint::refund(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            parameters,
-            self.get_api_config().get_custom_headers().clone(),
+            &to_parameters(parameters)?,
+            custom_headers,
             self.get_api_config().get_proxies().clone()
         )?;
 
         Ok(response)
     }
 
-    fn refund_direct(&self, transaction_id: String, parameters: &str) -> MidtransResult {
-        let api_url = format!(
-            "{}/v2/{}/refund/online/direct",
-            self.get_api_config().get_core_api_base_url(),
-            transaction_id
-        );
+    fn refund_direct<P: Parameters + ?Sized>(&self, transaction_id: String, parameters: &P) -> MidtransResult where Self: Sized {
+        self.refund_direct_with_options(transaction_id, parameters, &RequestOptions::default())
+    }
+
+    /// `refund_direct` with per-request [`RequestOptions`], e.g. `Idempotency-Key`
+    fn refund_direct_with_options<P: Parameters + ?Sized>(&self, transaction_id: String, parameters: &P, options: &RequestOptions) -> MidtransResult where Self: Sized {
+        let custom_headers = options.merge_headers(
+            self.get_api_config().get_custom_headers(),
+            self.get_api_config().get_idempotency_key_generator()
+        )?;
 
         let response = self.request(
-            Method::POST,
+            Endpoint::refund_direct(self.get_api_config(), &transaction_id),
             self.get_api_config().get_server_key(),
-            &api_url,
-            parameters,
-            self.get_api_config().get_custom_headers().clone(),
+            &to_parameters(parameters)?,
+            custom_headers,
             self.get_api_config().get_proxies().clone()
         )?;
 
         Ok(response)
     }
 
+    /// `status` returning a typed [`TransactionStatusResponse`]
+    fn status_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.status(transaction_id)?)
+    }
+
+    /// `statusb2b` returning a typed [`TransactionStatusB2bResponse`]
+    fn statusb2b_typed(&self, transaction_id: String) -> Result<TransactionStatusB2bResponse, MidtransError> {
+        from_response(self.statusb2b(transaction_id)?)
+    }
+
+    /// `approve` returning a typed [`TransactionStatusResponse`]
+    fn approve_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.approve(transaction_id)?)
+    }
+
+    /// `deny` returning a typed [`TransactionStatusResponse`]
+    fn deny_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.deny(transaction_id)?)
+    }
+
+    /// `cancel` returning a typed [`TransactionStatusResponse`]
+    fn cancel_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.cancel(transaction_id)?)
+    }
+
+    /// `expire` returning a typed [`TransactionStatusResponse`]
+    fn expire_typed(&self, transaction_id: String) -> Result<TransactionStatusResponse, MidtransError> {
+        from_response(self.expire(transaction_id)?)
+    }
+
+    /// `refund` returning a typed [`RefundResponse`]
+    fn refund_typed<P: Parameters + ?Sized>(&self, transaction_id: String, parameters: &P) -> Result<RefundResponse, MidtransError> where Self: Sized {
+        from_response(self.refund(transaction_id, parameters)?)
+    }
+
+    /// `refund_direct` returning a typed [`RefundResponse`]
+    fn refund_direct_typed<P: Parameters + ?Sized>(&self, transaction_id: String, parameters: &P) -> Result<RefundResponse, MidtransError> where Self: Sized {
+        from_response(self.refund_direct(transaction_id, parameters)?)
+    }
+
+    /// Verify the `signature_key` of a notification with the server key of `ApiConfig`,
+    /// without any request to Midtrans
+    fn verify_signature(&self, notification: &HashMap<String, Value>) -> Result<(), MidtransError> {
+        signature::verify_notification(notification, self.get_api_config().get_server_key())
+    }
+
+    /// Get the status of the transaction a notification refers to, by its
+    /// `transaction_id` or `order_id` when `transaction_id` is absent or null
     fn notification_from_json(&self, notification: HashMap<String, Value>) -> MidtransResult {
-        let transaction_id = notification["transaction_id"].as_str().unwrap();
-        self.status(transaction_id.to_string())
+        let transaction_id = notification_transaction_id(&notification)?;
+        self.status(transaction_id)
     }
 
     fn notification_from_str(&self, notification: &str) -> MidtransResult {
         let notification: HashMap<String, Value> = serde_json::from_str(notification)?;
         self.notification_from_json(notification)
     }
-}
\ No newline at end of file
+}
+
+/// Pick the id to query a notification by: `transaction_id`, or `order_id` when it is absent or null
+pub(crate) fn notification_transaction_id(notification: &HashMap<String, Value>) -> Result<String, MidtransError> {
+    let present = |name| notification.get(name).filter(|value| !value.is_null());
+    let (field, value) = match present("transaction_id") {
+        Some(value) => ("transaction_id", value),
+        None => match present("order_id") {
+            Some(value) => ("order_id", value),
+            None => return Err(MidtransError::InvalidNotification(
+                "missing field `transaction_id` or `order_id`".to_string()
+            ))
+        }
+    };
+
+    let id = value.as_str().ok_or_else(|| MidtransError::InvalidNotification(
+        format!("field `{}` must be a string", field)
+    ))?;
+
+    // the id ends up in the status url path, so keep it to the characters Midtrans allows
+    match is_path_segment(id) {
+        true => Ok(id.to_string()),
+        false => Err(MidtransError::InvalidNotification(
+            format!("field `{}` is not a valid id", field)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use reqwest::Method;
+    use crate::CoreApi;
+    use crate::transport::FakeTransport;
+
+    fn notification(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn error_message(value: Value) -> String {
+        match notification_transaction_id(&notification(value)) {
+            Err(MidtransError::InvalidNotification(msg)) => msg,
+            other => panic!("unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn transaction_id_or_order_id() {
+        let id = notification_transaction_id(&notification(json!({
+            "transaction_id": "73ebe57c-ffb0-42a6-93fa-c5022a7f316e",
+            "order_id": "order-1"
+        })));
+        assert_eq!(id.unwrap(), "73ebe57c-ffb0-42a6-93fa-c5022a7f316e");
+
+        let id = notification_transaction_id(&notification(json!({"order_id": "order-1"})));
+        assert_eq!(id.unwrap(), "order-1");
+
+        let id = notification_transaction_id(&notification(json!({
+            "transaction_id": null,
+            "order_id": "order-1"
+        })));
+        assert_eq!(id.unwrap(), "order-1");
+    }
+
+    #[test]
+    fn missing_field() {
+        assert!(error_message(json!({})).contains("`transaction_id` or `order_id`"));
+        assert!(error_message(json!({"transaction_id": null, "order_id": null})).contains("`transaction_id` or `order_id`"));
+    }
+
+    #[test]
+    fn mistyped_field() {
+        assert!(error_message(json!({"transaction_id": 1234})).contains("`transaction_id` must be a string"));
+        assert!(error_message(json!({"order_id": ["order-1"]})).contains("`order_id` must be a string"));
+    }
+
+    #[test]
+    fn invalid_id() {
+        assert!(error_message(json!({"transaction_id": ""})).contains("`transaction_id`"));
+        assert!(error_message(json!({"order_id": "../v1/subscriptions"})).contains("`order_id`"));
+    }
+
+    #[test]
+    fn notification_from_json_without_id() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        let response = core.notification_from_json(serde_json::from_str("{}")?);
+        assert!(matches!(response, Err(MidtransError::InvalidNotification(_))));
+        assert!(transport.requests().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn notification_from_json_with_order_id_only() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200", "order_id": "order-1"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        let notification = serde_json::from_str(r#"{"order_id": "order-1"}"#)?;
+        core.notification_from_json(notification)?;
+        let request = transport.last_request();
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.url, "https://api.sandbox.midtrans.com/v2/order-1/status");
+        Ok(())
+    }
+}
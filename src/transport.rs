@@ -0,0 +1,226 @@
+//! Http Transport
+//!
+//! [`Transport`] is the extension point used by [`crate::MidtransClient`] to
+//! actually send a request over the wire. The default implementation is
+//! [`ReqwestTransport`], but any HTTP stack (or an in-memory fake for tests)
+//! can be plugged into `CoreApi` / `Snap` through their builders.
+
+use std::time::Duration;
+use reqwest::{header::HeaderMap, Method, Proxy};
+use crate::{ApiConfig, MidtransError, http_client::HttpClient};
+
+/// Fully prepared HTTP request, including auth and default headers
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+    /// Timeout of the whole request, transports should fail with
+    /// `MidtransError::TimeoutError` when it is exceeded
+    pub timeout: Option<Duration>
+}
+
+/// Raw HTTP response returned by a [`Transport`]
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+    pub status_code: u16,
+    pub headers: HeaderMap,
+    pub body: String
+}
+
+/// Send a [`TransportRequest`] and return the raw [`TransportResponse`].
+///
+/// Implementations should only fail on transport level problems (connection,
+/// TLS, ...), Midtrans API errors are decoded from the response afterwards.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError>;
+}
+
+/// Default [`Transport`] backed by `reqwest::blocking::Client`.
+///
+/// The clients build it once so every request reuses the same connection pool. When it wraps a
+/// preconfigured client (`http_client` of the builders) the builder's `proxies` are ignored,
+/// configure them on that client instead. The same holds for the async `ReqwestTransport`.
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client
+}
+
+impl ReqwestTransport {
+    pub fn new(proxies: Option<Proxy>) -> Result<Self, MidtransError> {
+        let mut client = HttpClient::new();
+        if let Some(proxies) = proxies {
+            client.proxies(proxies);
+        }
+        Ok(Self { client: client.build()? })
+    }
+
+    /// Build transport with proxies and connect timeout of `api_config`
+    pub fn from_config(api_config: &ApiConfig) -> Result<Self, MidtransError> {
+        let mut client = HttpClient::new();
+        if let Some(proxies) = api_config.get_proxies() {
+            client.proxies(proxies.clone());
+        }
+        if let Some(timeout) = api_config.get_connect_timeout() {
+            client.connect_timeout(timeout);
+        }
+        Ok(Self { client: client.build()? })
+    }
+}
+
+impl From<reqwest::blocking::Client> for ReqwestTransport {
+    fn from(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        let mut request_builder = self.client
+            .request(request.method, request.url)
+            .headers(request.headers);
+
+        if let Some(body) = request.body {
+            request_builder = request_builder.body(body);
+        }
+
+        if let Some(timeout) = request.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder.send()?;
+        let headers = response.headers().clone();
+        let status_code = response.status().as_u16();
+        let body = response.text()?;
+
+        Ok(TransportResponse { status_code, headers, body })
+    }
+}
+
+/// Status code and body of a [`FakeTransport`] response to the given request
+#[cfg(test)]
+type Respond = Box<dyn Fn(&TransportRequest) -> (u16, String) + Send + Sync>;
+
+/// In-memory [`Transport`] of the tests, answering with `respond` and keeping the sent requests
+#[cfg(test)]
+pub(crate) struct FakeTransport {
+    respond: Respond,
+    requests: std::sync::Mutex<Vec<TransportRequest>>
+}
+
+#[cfg(test)]
+impl FakeTransport {
+    /// Answers every request with `status_code` and `body`
+    pub(crate) fn new(status_code: u16, body: impl ToString) -> std::sync::Arc<Self> {
+        let body = body.to_string();
+        Self::with(move |_| (status_code, body.clone()))
+    }
+
+    pub(crate) fn with(
+        respond: impl Fn(&TransportRequest) -> (u16, String) + Send + Sync + 'static
+    ) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Self { respond: Box::new(respond), requests: Default::default() })
+    }
+
+    pub(crate) fn requests(&self) -> Vec<TransportRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub(crate) fn last_request(&self) -> TransportRequest {
+        self.requests().pop().expect("a request was sent")
+    }
+
+    fn respond(&self, request: TransportRequest) -> TransportResponse {
+        let (status_code, body) = (self.respond)(&request);
+        self.requests.lock().unwrap().push(request);
+        TransportResponse { status_code, headers: HeaderMap::new(), body }
+    }
+}
+
+#[cfg(test)]
+impl Transport for FakeTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        Ok(self.respond(request))
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+#[async_trait::async_trait]
+impl crate::asynchronous::Transport for FakeTransport {
+    async fn send(&self, request: TransportRequest) -> Result<TransportResponse, MidtransError> {
+        Ok(self.respond(request))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CoreApi, Snap, Transactions};
+
+    #[test]
+    fn charge_uses_custom_transport() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "201", "transaction_status": "pending"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        let response = core.charge(r#"{"payment_type": "gopay"}"#)?;
+        assert_eq!(response["transaction_status"], "pending");
+
+        let request = transport.last_request();
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.url, "https://api.sandbox.midtrans.com/v2/charge");
+        assert_eq!(request.headers["authorization"], "Basic c2VydmVyX2tleTo=");
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.body.unwrap(), r#"{"payment_type":"gopay"}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn get_parameters_sent_as_query() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(200, r#"{"status_code": "200"}"#);
+        let core = CoreApi::new(false, "server_key".to_string())
+            .transport(transport.clone())
+            .build()?;
+        core.card_token(r#"{"card_cvv": "123"}"#)?;
+
+        let request = transport.last_request();
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.url, "https://api.sandbox.midtrans.com/v2/token?card_cvv=123");
+        assert!(request.body.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn api_error_from_custom_transport() -> Result<(), MidtransError> {
+        let transport = FakeTransport::new(404, r#"{"status_code": "404"}"#);
+        let mut snap = Snap::new(false, "server_key".to_string()).build()?;
+        snap.set_transport(transport.clone());
+        let response = snap.status("non-exist-order-id".to_string());
+        assert!(matches!(response, Err(MidtransError::ApiError(ref e)) if e.status_code == 404));
+        assert_eq!(
+            transport.last_request().url,
+            "https://api.sandbox.midtrans.com/v2/non-exist-order-id/status"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn request_timeout() -> Result<(), MidtransError> {
+        // accept the connection but never respond
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v2/order-id/status", listener.local_addr().unwrap());
+        let api_config = ApiConfig::new(false, "server_key".to_string()).build();
+        let transport = ReqwestTransport::from_config(&api_config)?;
+        let request = TransportRequest {
+            method: Method::GET,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: Some(std::time::Duration::from_millis(100))
+        };
+        let response = transport.send(request);
+        assert!(matches!(response, Err(MidtransError::TimeoutError(_))));
+        Ok(())
+    }
+
+}
@@ -0,0 +1,93 @@
+//! actix-web Integration
+//!
+//! Available with the `actix` cargo feature. Register a [`MidtransWebhook`]
+//! as app data, then take [`VerifiedNotification`] as a handler argument.
+//!
+//! ```no_run
+//! use actix_web::{web, App, HttpResponse};
+//! use midtransclient::webhook::{MidtransWebhook, VerifiedNotification};
+//!
+//! async fn notification(event: VerifiedNotification) -> HttpResponse {
+//!     println!("{:?}", event.state());
+//!     HttpResponse::Ok().finish()
+//! }
+//!
+//! let webhook = web::Data::new(MidtransWebhook::new("YOUR_SERVER_KEY".to_string()));
+//! let app = App::new()
+//!     .app_data(webhook)
+//!     .route("/midtrans/notification", web::post().to(notification));
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use actix_web::{dev::Payload, error, web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use actix_web::http::StatusCode;
+use crate::MidtransError;
+use super::{MidtransWebhook, VerifiedNotification, WebhookRejection};
+
+impl ResponseError for WebhookRejection {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(WebhookRejection::status_code(self))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(ResponseError::status_code(self)).body(self.to_string())
+    }
+}
+
+impl FromRequest for VerifiedNotification {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let webhook = req.app_data::<web::Data<MidtransWebhook>>().cloned();
+        let body = web::Bytes::from_request(req, payload);
+
+        Box::pin(async move {
+            let webhook = webhook.ok_or_else(|| error::ErrorInternalServerError(
+                "MidtransWebhook is not registered as app data"
+            ))?;
+            let body = body.await.map_err(|e| WebhookRejection(
+                MidtransError::InvalidNotification(e.to_string())
+            ))?;
+            Ok(webhook.verify(&body).await.map_err(WebhookRejection)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use crate::webhook::test::{SERVER_KEY, notification_body};
+
+    async fn extract(body: Vec<u8>, webhook: Option<MidtransWebhook>) -> Result<VerifiedNotification, actix_web::Error> {
+        let mut request = TestRequest::post().set_payload(body);
+        if let Some(webhook) = webhook {
+            request = request.app_data(web::Data::new(webhook));
+        }
+        let (req, mut payload) = request.to_http_parts();
+        VerifiedNotification::from_request(&req, &mut payload).await
+    }
+
+    #[actix_web::test]
+    async fn extract_verified_notification() {
+        let webhook = MidtransWebhook::new(SERVER_KEY.to_string());
+        let notification = extract(notification_body(SERVER_KEY), Some(webhook)).await.unwrap();
+        assert!(notification.state().unwrap().is_paid());
+    }
+
+    #[actix_web::test]
+    async fn reject_forged_notification() {
+        let webhook = MidtransWebhook::new(SERVER_KEY.to_string());
+        let error = extract(notification_body("another-key"), Some(webhook)).await.unwrap_err();
+        assert_eq!(error.as_response_error().status_code(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn missing_webhook_config() {
+        let error = extract(notification_body(SERVER_KEY), None).await.unwrap_err();
+        assert_eq!(error.as_response_error().status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
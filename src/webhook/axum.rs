@@ -0,0 +1,123 @@
+//! axum Integration
+//!
+//! Available with the `axum` cargo feature.
+//!
+//! ```no_run
+//! use axum::http::StatusCode;
+//! use midtransclient::webhook::{MidtransWebhook, VerifiedNotification};
+//!
+//! async fn notification(event: VerifiedNotification) -> StatusCode {
+//!     println!("{:?}", event.state());
+//!     StatusCode::OK
+//! }
+//!
+//! # async fn run() {
+//! let app: axum::Router = axum::Router::new()
+//!     .route("/midtrans/notification", axum::routing::post(notification))
+//!     .with_state(MidtransWebhook::new("YOUR_SERVER_KEY".to_string()));
+//! # }
+//! ```
+
+use std::future::Future;
+use axum::async_trait;
+use axum::body::Bytes;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use crate::MidtransError;
+use super::{MidtransWebhook, VerifiedNotification, WebhookRejection};
+
+impl IntoResponse for WebhookRejection {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status_code())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, self.0.to_string()).into_response()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for VerifiedNotification
+where
+    MidtransWebhook: FromRef<S>,
+    S: Send + Sync
+{
+    type Rejection = WebhookRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state).await
+            .map_err(|e| WebhookRejection(MidtransError::InvalidNotification(e.body_text())))?;
+        MidtransWebhook::from_ref(state).verify(&body).await.map_err(WebhookRejection)
+    }
+}
+
+/// Router handling POST notifications on `path`
+///
+/// `callback` is called once per verified notification and its response is sent
+/// back to Midtrans as is. Answer `200` to acknowledge the notification, any other
+/// status makes Midtrans deliver it again later.
+pub fn webhook_router<F, Fut, R>(path: &str, webhook: MidtransWebhook, callback: F) -> Router
+where
+    F: Fn(VerifiedNotification) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = R> + Send + 'static,
+    R: IntoResponse + 'static
+{
+    let handler = move |notification: VerifiedNotification| async move {
+        callback(notification).await
+    };
+    Router::new()
+        .route(path, post(handler))
+        .with_state(webhook)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+    use crate::webhook::test::{SERVER_KEY, notification_body};
+
+    fn request(body: Vec<u8>) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri("/notification")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn extract_verified_notification() {
+        let webhook = MidtransWebhook::new(SERVER_KEY.to_string());
+        let result = VerifiedNotification::from_request(request(notification_body(SERVER_KEY)), &webhook).await;
+        assert!(result.unwrap().state().unwrap().is_paid());
+    }
+
+    #[tokio::test]
+    async fn reject_forged_notification() {
+        let webhook = MidtransWebhook::new(SERVER_KEY.to_string());
+        let result = VerifiedNotification::from_request(request(notification_body("another-key")), &webhook).await;
+        let response = result.unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn router_answers_with_callback_response() {
+        let router = webhook_router("/notification", MidtransWebhook::new(SERVER_KEY.to_string()), |_| async {
+            Err::<(), _>(StatusCode::SERVICE_UNAVAILABLE)
+        });
+        let response = router.clone().oneshot(request(notification_body(SERVER_KEY))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = router.oneshot(request(notification_body("another-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn reject_malformed_notification() {
+        let webhook = MidtransWebhook::new(SERVER_KEY.to_string());
+        let result = VerifiedNotification::from_request(request(b"{".to_vec()), &webhook).await;
+        let response = result.unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,218 @@
+//! Webhook
+//!
+//! Shared handling of Midtrans HTTP notifications for web framework integrations:
+//! decode the body, verify its `signature_key` and parse it into a typed
+//! [`Notification`]. Framework specific extractors are available behind the
+//! `axum` and `actix` cargo features.
+
+pub mod store;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "actix")]
+pub mod actix;
+
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "async")]
+use std::sync::Arc;
+use serde_json::Value;
+use crate::MidtransError;
+#[cfg(feature = "async")]
+use crate::{ApiConfig, asynchronous::Transactions};
+use crate::models::{Notification, TransactionState, TransactionStatusResponse};
+use crate::signature;
+
+/// Notification whose `signature_key` has been verified
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedNotification {
+    /// Typed notification
+    pub notification: Notification,
+    /// Decoded notification body
+    pub body: HashMap<String, Value>,
+    /// Status re-fetched from Midtrans, when confirmation is enabled
+    pub status: Option<TransactionStatusResponse>
+}
+
+impl VerifiedNotification {
+    /// Decode `body`, verify its `signature_key` with `server_key` and parse it
+    ///
+    /// Notifications without a `signature_key` are rejected. Subscription and pay
+    /// account notifications never carry one, so they are rejected with
+    /// `MidtransError::InvalidNotification` (`400`, Midtrans doesn't retry them);
+    /// fetch their state with `CoreApi::get_subscription` / `get_payment_account` instead.
+    pub fn from_body(body: &[u8], server_key: &str) -> Result<Self, MidtransError> {
+        let body: HashMap<String, Value> = serde_json::from_slice(body)?;
+        let notification = Notification::from_json(body.clone())?;
+        if let Notification::Subscription(_) | Notification::PayAccount(_) = notification {
+            return Err(MidtransError::InvalidNotification(
+                "subscription and pay account notifications can't be verified".to_string()
+            ));
+        }
+        signature::verify_notification(&body, server_key)?;
+        Ok(Self { notification, body, status: None })
+    }
+
+    /// Transaction state, taken from the confirmed status when available
+    pub fn state(&self) -> Option<TransactionState> {
+        match &self.status {
+            Some(status) => TransactionState::from_response(status),
+            None => TransactionState::from_notification(&self.notification)
+        }
+    }
+
+    /// Re-fetch the transaction status from Midtrans with `client`
+    ///
+    /// Does nothing for unknown notifications.
+    #[cfg(feature = "async")]
+    pub async fn confirm(
+        &mut self,
+        client: &(dyn crate::asynchronous::Transactions + Send)
+    ) -> Result<(), MidtransError> {
+        if let Some(transaction) = self.notification.transaction() {
+            let status = client.status_typed(transaction.transaction_id.clone()).await?;
+            self.status = Some(status);
+        }
+        Ok(())
+    }
+}
+
+/// Webhook configuration shared by the framework extractors
+///
+/// Build it from the `api_config` of either `CoreApi` or `Snap`.
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct MidtransWebhook {
+    server_key: String,
+    confirm: Option<Arc<dyn Transactions + Send>>
+}
+
+#[cfg(feature = "async")]
+impl MidtransWebhook {
+    /// Verify notifications with `server_key`
+    pub fn new(server_key: String) -> Self {
+        Self { server_key, confirm: None }
+    }
+
+    /// Verify notifications with the server key of `api_config`
+    pub fn from_config(api_config: &ApiConfig) -> Self {
+        Self::new(api_config.get_server_key().to_string())
+    }
+
+    /// Re-fetch the transaction status with `client` after verifying the signature,
+    /// see [`VerifiedNotification::confirm`]
+    pub fn confirm_status(mut self, client: Arc<dyn Transactions + Send>) -> Self {
+        self.confirm = Some(client);
+        self
+    }
+
+    /// Verify, parse and optionally confirm a notification body
+    pub async fn verify(&self, body: &[u8]) -> Result<VerifiedNotification, MidtransError> {
+        let mut notification = VerifiedNotification::from_body(body, &self.server_key)?;
+        if let Some(client) = &self.confirm {
+            notification.confirm(client.as_ref()).await?;
+        }
+        Ok(notification)
+    }
+}
+
+#[cfg(feature = "async")]
+impl fmt::Debug for MidtransWebhook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MidtransWebhook")
+            .field("confirm", &self.confirm.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Rejection of the [`VerifiedNotification`] extractors,
+/// responding with the status code Midtrans expects
+#[derive(Debug)]
+pub struct WebhookRejection(pub MidtransError);
+
+impl WebhookRejection {
+    /// See [`error_status_code`]
+    pub fn status_code(&self) -> u16 {
+        error_status_code(&self.0)
+    }
+}
+
+impl fmt::Display for WebhookRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for WebhookRejection {}
+
+/// HTTP status code Midtrans expects for a notification that failed with `error`
+///
+/// Client errors (4xx) stop Midtrans from retrying, server errors (5xx) make
+/// it deliver the notification again later.
+pub fn error_status_code(error: &MidtransError) -> u16 {
+    match error {
+        MidtransError::InvalidSignature => 403,
+        MidtransError::JsonDecodeError(_) | MidtransError::InvalidNotification(_) => 400,
+        _ => 500
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    pub(crate) const SERVER_KEY: &str = "SB-Mid-server-TEST";
+
+    pub(crate) fn notification_body(server_key: &str) -> Vec<u8> {
+        json!({
+            "transaction_id": "9aed5972-5b6a-401e-894b-a32c91ed1a3a",
+            "order_id": "order-123",
+            "status_code": "200",
+            "gross_amount": "10000.00",
+            "payment_type": "bank_transfer",
+            "transaction_status": "settlement",
+            "va_numbers": [{"bank": "bca", "va_number": "812785002530231"}],
+            "signature_key": signature::signature_key("order-123", "200", "10000.00", server_key)
+        }).to_string().into_bytes()
+    }
+
+    #[test]
+    fn verified() {
+        let verified = VerifiedNotification::from_body(&notification_body(SERVER_KEY), SERVER_KEY).unwrap();
+        assert!(matches!(verified.notification, Notification::BankTransfer(_)));
+        assert!(verified.state().unwrap().is_paid());
+    }
+
+    #[test]
+    fn rejected() {
+        let forged = VerifiedNotification::from_body(&notification_body("another-key"), SERVER_KEY);
+        assert_eq!(error_status_code(&forged.unwrap_err()), 403);
+
+        let malformed = VerifiedNotification::from_body(b"not json", SERVER_KEY);
+        assert_eq!(error_status_code(&malformed.unwrap_err()), 400);
+    }
+
+    #[test]
+    fn subscription_rejected() {
+        let body = json!({
+            "id": "d137e9b5-5b34-4a3c-a1c4-d1d1b6a2cd0c",
+            "name": "MONTHLY_2024",
+            "amount": "14000",
+            "currency": "IDR",
+            "status": "active",
+            "token": "481111xDUgxnnredRMAXuklkvAON1114",
+            "payment_type": "credit_card",
+            "schedule": {
+                "interval": 1,
+                "interval_unit": "month",
+                "start_time": "2024-01-01 07:25:01",
+                "next_execution_at": "2024-02-01 07:25:01"
+            }
+        }).to_string().into_bytes();
+        let result = VerifiedNotification::from_body(&body, SERVER_KEY);
+        match result {
+            Err(error @ MidtransError::InvalidNotification(_)) => assert_eq!(error_status_code(&error), 400),
+            _ => panic!("expected InvalidNotification")
+        }
+    }
+}
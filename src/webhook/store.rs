@@ -0,0 +1,425 @@
+//! Notification Store
+//!
+//! Midtrans retries HTTP notifications until it gets a `200`, so the same event
+//! can arrive several times. A [`NotificationStore`] remembers which events are
+//! being or have been processed, and [`process_once`] runs a callback once per
+//! distinct event.
+
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::MidtransError;
+use super::VerifiedNotification;
+
+/// Identity of a notification event: transaction id, status and signature
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NotificationKey {
+    pub id: String,
+    pub status: String,
+    pub signature: String
+}
+
+impl NotificationKey {
+    pub fn new(id: String, status: String, signature: String) -> Self {
+        Self { id, status, signature }
+    }
+
+    /// Key of a decoded notification body
+    ///
+    /// The id is `transaction_id`, falling back to `order_id`, subscription `id`
+    /// or `account_id`. The status is `transaction_status`, falling back to
+    /// subscription `status` or `account_status`.
+    pub fn from_json(notification: &HashMap<String, Value>) -> Result<Self, MidtransError> {
+        let first = |fields: &[&str]| fields.iter()
+            .find_map(|field| notification.get(*field).and_then(Value::as_str))
+            .map(str::to_string);
+
+        let id = first(&["transaction_id", "order_id", "id", "account_id"]).ok_or_else(|| {
+            MidtransError::InvalidNotification("missing field `transaction_id` or `order_id`".to_string())
+        })?;
+        let status = first(&["transaction_status", "status", "account_status"]).ok_or_else(|| {
+            MidtransError::InvalidNotification("missing field `transaction_status`".to_string())
+        })?;
+        let signature = first(&["signature_key"]).unwrap_or_default();
+
+        Ok(Self::new(id, status, signature))
+    }
+}
+
+impl fmt::Display for NotificationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.id, self.status, self.signature)
+    }
+}
+
+/// Processing state of a notification event in a [`NotificationStore`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationState {
+    /// Never claimed, or removed after its callback failed
+    New,
+    /// Claimed by a callback that hasn't finished yet
+    InFlight,
+    /// Processed successfully
+    Done
+}
+
+/// Storage of notification keys being processed and processed
+pub trait NotificationStore: Send + Sync {
+    /// Claim `key` for processing, returning its state before the call.
+    /// A `New` key becomes `InFlight`, the others are left as they are.
+    /// Must be atomic, so only one caller gets `New` for the same key.
+    fn claim(&self, key: &NotificationKey) -> Result<NotificationState, MidtransError>;
+
+    /// Mark a claimed `key` as processed
+    fn complete(&self, key: &NotificationKey) -> Result<(), MidtransError>;
+
+    /// Forget `key`, so the event is processed again on the next delivery
+    fn remove(&self, key: &NotificationKey) -> Result<(), MidtransError>;
+
+    fn state(&self, key: &NotificationKey) -> Result<NotificationState, MidtransError>;
+}
+
+/// Error of a duplicate delivery while the first one is still being processed
+///
+/// It is returned as `MidtransError::NotificationStoreError`, answered with `500`
+/// by [`super::error_status_code`] so Midtrans delivers the event again later,
+/// in case the first run fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationInFlight(pub NotificationKey);
+
+impl fmt::Display for NotificationInFlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "notification {} is still being processed", self.0)
+    }
+}
+
+impl error::Error for NotificationInFlight {}
+
+/// Claim `key`, `Ok(false)` for an event already processed
+fn claim(store: &dyn NotificationStore, key: &NotificationKey) -> Result<bool, MidtransError> {
+    match store.claim(key)? {
+        NotificationState::New => Ok(true),
+        NotificationState::Done => Ok(false),
+        NotificationState::InFlight => Err(MidtransError::NotificationStoreError(
+            Box::new(NotificationInFlight(key.clone()))
+        ))
+    }
+}
+
+/// Complete or remove the claimed `key` depending on the callback `result`
+fn finish<T, E: From<MidtransError>>(store: &dyn NotificationStore, key: &NotificationKey, result: Result<T, E>) -> Result<Option<T>, E> {
+    match result {
+        Ok(value) => {
+            store.complete(key)?;
+            Ok(Some(value))
+        },
+        Err(err) => {
+            store.remove(key)?;
+            Err(err)
+        }
+    }
+}
+
+/// Run `callback` only if the event of `key` hasn't been processed yet
+///
+/// Returns `Ok(None)` for an event already processed, and [`NotificationInFlight`]
+/// for one still being processed. When `callback` fails the key is removed again,
+/// so a later delivery of the same event is processed.
+pub fn process_once<T, E, F>(store: &dyn NotificationStore, key: &NotificationKey, callback: F) -> Result<Option<T>, E>
+where
+    F: FnOnce() -> Result<T, E>,
+    E: From<MidtransError>
+{
+    if !claim(store, key)? {
+        return Ok(None)
+    }
+    finish(store, key, callback())
+}
+
+/// [`process_once`] for an async `callback`
+#[cfg(feature = "async")]
+pub async fn process_once_async<T, E, F, Fut>(store: &dyn NotificationStore, key: &NotificationKey, callback: F) -> Result<Option<T>, E>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: From<MidtransError>
+{
+    if !claim(store, key)? {
+        return Ok(None)
+    }
+    finish(store, key, callback().await)
+}
+
+impl VerifiedNotification {
+    /// [`NotificationKey`] of this notification
+    pub fn key(&self) -> Result<NotificationKey, MidtransError> {
+        NotificationKey::from_json(&self.body)
+    }
+}
+
+/// In-memory [`NotificationStore`], forgotten on restart
+#[derive(Debug, Default)]
+pub struct MemoryNotificationStore {
+    keys: Mutex<HashMap<NotificationKey, NotificationState>>
+}
+
+impl MemoryNotificationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl NotificationStore for MemoryNotificationStore {
+    fn claim(&self, key: &NotificationKey) -> Result<NotificationState, MidtransError> {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        match keys.get(key) {
+            Some(state) => Ok(*state),
+            None => {
+                keys.insert(key.clone(), NotificationState::InFlight);
+                Ok(NotificationState::New)
+            }
+        }
+    }
+
+    fn complete(&self, key: &NotificationKey) -> Result<(), MidtransError> {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone(), NotificationState::Done);
+        Ok(())
+    }
+
+    fn remove(&self, key: &NotificationKey) -> Result<(), MidtransError> {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+        Ok(())
+    }
+
+    fn state(&self, key: &NotificationKey) -> Result<NotificationState, MidtransError> {
+        let keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(keys.get(key).copied().unwrap_or(NotificationState::New))
+    }
+}
+
+/// [`NotificationStore`] persisted in a file, one JSON encoded key per line
+///
+/// Processed keys are loaded into memory when opened and appended when completed.
+/// Keys in flight are only kept in memory, so an event whose callback was interrupted
+/// by a restart is processed again. The file must not be shared between processes.
+#[derive(Debug)]
+pub struct FileNotificationStore {
+    path: PathBuf,
+    keys: Mutex<FileKeys>
+}
+
+#[derive(Debug, Default)]
+struct FileKeys {
+    done: HashSet<NotificationKey>,
+    in_flight: HashSet<NotificationKey>
+}
+
+impl FileNotificationStore {
+    /// Open the store at `path`, creating the file if it doesn't exist
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MidtransError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).read(true).open(&path).map_err(store_error)?;
+        let mut done = HashSet::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(store_error)?;
+            if !line.is_empty() {
+                let key = serde_json::from_str(&line)
+                    .map_err(|e| MidtransError::NotificationStoreError(Box::new(e)))?;
+                done.insert(key);
+            }
+        }
+        Ok(Self { path, keys: Mutex::new(FileKeys { done, in_flight: HashSet::new() }) })
+    }
+
+    fn write_line(file: &mut File, key: &NotificationKey) -> Result<(), MidtransError> {
+        let line = serde_json::to_string(key)?;
+        writeln!(file, "{}", line).map_err(store_error)
+    }
+}
+
+impl NotificationStore for FileNotificationStore {
+    fn claim(&self, key: &NotificationKey) -> Result<NotificationState, MidtransError> {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        if keys.done.contains(key) {
+            return Ok(NotificationState::Done)
+        }
+        match keys.in_flight.insert(key.clone()) {
+            true => Ok(NotificationState::New),
+            false => Ok(NotificationState::InFlight)
+        }
+    }
+
+    fn complete(&self, key: &NotificationKey) -> Result<(), MidtransError> {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        if keys.done.contains(key) {
+            return Ok(())
+        }
+        let mut file = OpenOptions::new().append(true).open(&self.path).map_err(store_error)?;
+        Self::write_line(&mut file, key)?;
+        file.sync_data().map_err(store_error)?;
+        keys.in_flight.remove(key);
+        keys.done.insert(key.clone());
+        Ok(())
+    }
+
+    fn remove(&self, key: &NotificationKey) -> Result<(), MidtransError> {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        keys.in_flight.remove(key);
+        if !keys.done.remove(key) {
+            return Ok(())
+        }
+        // rewrite through a temporary file, so a crash never leaves a truncated store
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp_path).map_err(store_error)?;
+        for key in keys.done.iter() {
+            Self::write_line(&mut file, key)?;
+        }
+        file.sync_all().map_err(store_error)?;
+        fs::rename(&tmp_path, &self.path).map_err(store_error)
+    }
+
+    fn state(&self, key: &NotificationKey) -> Result<NotificationState, MidtransError> {
+        let keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        match (keys.done.contains(key), keys.in_flight.contains(key)) {
+            (true, _) => Ok(NotificationState::Done),
+            (false, true) => Ok(NotificationState::InFlight),
+            (false, false) => Ok(NotificationState::New)
+        }
+    }
+}
+
+fn store_error(err: std::io::Error) -> MidtransError {
+    MidtransError::NotificationStoreError(Box::new(err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use serde_json::json;
+
+    fn key(status: &str) -> NotificationKey {
+        NotificationKey::new("trx-1".to_string(), status.to_string(), "signature".to_string())
+    }
+
+    #[test]
+    fn key_from_json() {
+        let notification = serde_json::from_value(json!({
+            "order_id": "order-1",
+            "transaction_status": "settlement",
+            "signature_key": "abc"
+        })).unwrap();
+        let key = NotificationKey::from_json(&notification).unwrap();
+        assert_eq!(key.to_string(), "order-1:settlement:abc");
+    }
+
+    #[test]
+    fn process_once_skips_duplicates() {
+        let store = MemoryNotificationStore::new();
+        let mut calls = 0;
+        for _ in 0..3 {
+            let _ = process_once(&store, &key("settlement"), || -> Result<(), MidtransError> {
+                calls += 1;
+                Ok(())
+            });
+        }
+        let result = process_once(&store, &key("refund"), || -> Result<(), MidtransError> {
+            calls += 1;
+            Ok(())
+        });
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn process_once_retries_failed_callback() {
+        let store = MemoryNotificationStore::new();
+        let failed = process_once(&store, &key("settlement"), || -> Result<(), MidtransError> {
+            Err(MidtransError::InvalidNotification("database is down".to_string()))
+        });
+        assert!(failed.is_err());
+        assert_eq!(store.state(&key("settlement")).unwrap(), NotificationState::New);
+
+        let retried = process_once(&store, &key("settlement"), || -> Result<u8, MidtransError> { Ok(1) });
+        assert_eq!(retried.unwrap(), Some(1));
+    }
+
+    #[test]
+    fn duplicate_in_flight_is_retryable() {
+        let store = MemoryNotificationStore::new();
+        let first = process_once(&store, &key("settlement"), || -> Result<(), MidtransError> {
+            // Midtrans delivers the same event again while the first run is still busy
+            let duplicate = process_once(&store, &key("settlement"), || -> Result<(), MidtransError> { Ok(()) });
+            match duplicate {
+                Err(ref e @ MidtransError::NotificationStoreError(ref inner)) => {
+                    assert!(inner.is::<NotificationInFlight>());
+                    assert_eq!(super::super::error_status_code(e), 500);
+                },
+                other => panic!("unexpected {:?}", other)
+            }
+            Err(MidtransError::InvalidNotification("database is down".to_string()))
+        });
+        assert!(first.is_err());
+
+        let retried = process_once(&store, &key("settlement"), || -> Result<u8, MidtransError> { Ok(1) });
+        assert_eq!(retried.unwrap(), Some(1));
+    }
+
+    fn temp_path() -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("midtrans-notifications-{}.log", nanos))
+    }
+
+    #[test]
+    fn file_store_persists() {
+        let path = temp_path();
+
+        let store = FileNotificationStore::open(&path).unwrap();
+        for status in ["pending", "settlement"] {
+            assert_eq!(store.claim(&key(status)).unwrap(), NotificationState::New);
+            store.complete(&key(status)).unwrap();
+        }
+        assert_eq!(store.claim(&key("settlement")).unwrap(), NotificationState::Done);
+        store.remove(&key("pending")).unwrap();
+        assert_eq!(store.claim(&key("refund")).unwrap(), NotificationState::New);
+        drop(store);
+
+        let store = FileNotificationStore::open(&path).unwrap();
+        assert_eq!(store.state(&key("settlement")).unwrap(), NotificationState::Done);
+        assert_eq!(store.state(&key("pending")).unwrap(), NotificationState::New);
+        // an interrupted callback is processed again after a restart
+        assert_eq!(store.state(&key("refund")).unwrap(), NotificationState::New);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_store_keys_escaped() {
+        let path = temp_path();
+        let keys = [
+            NotificationKey::new("trx-1\ntrx-2".to_string(), "settlement".to_string(), "".to_string()),
+            NotificationKey::new("trx-1\n{}".to_string(), "settlement".to_string(), "".to_string()),
+            NotificationKey::new("a:b".to_string(), "c".to_string(), "d".to_string()),
+            NotificationKey::new("a".to_string(), "b:c".to_string(), "d".to_string())
+        ];
+
+        let store = FileNotificationStore::open(&path).unwrap();
+        for key in &keys {
+            assert_eq!(store.claim(key).unwrap(), NotificationState::New);
+            store.complete(key).unwrap();
+        }
+        drop(store);
+
+        let store = FileNotificationStore::open(&path).unwrap();
+        for key in &keys {
+            assert_eq!(store.state(key).unwrap(), NotificationState::Done);
+        }
+        assert_eq!(store.state(&NotificationKey::new("trx-1".to_string(), "settlement".to_string(), "".to_string())).unwrap(), NotificationState::New);
+        fs::remove_file(&path).unwrap();
+    }
+}